@@ -0,0 +1,45 @@
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use alsein_parsers::parsers::{Error, ParserContext, RawParser, Set};
+
+const ROUNDS: u32 = 20;
+
+fn measure(f: impl Fn() -> usize) -> Duration {
+    let now = Instant::now();
+    for _ in 0..ROUNDS {
+        assert_eq!(f(), 1 << 20);
+    }
+    now.elapsed() / ROUNDS
+}
+
+fn main() {
+    let chars = "a".repeat(1 << 20).chars().collect::<Vec<_>>();
+    let pred: fn(char) -> bool = char::is_alphabetic;
+    let context = ParserContext::new();
+
+    let fast = context.chars_while(pred);
+    let fast = || fast.parse(&chars).unwrap().end;
+
+    let pred = black_box(pred);
+    let satisfy = move |input: &Vec<char>, start: usize| {
+        if start < Set::len(input) && pred(*Set::get(input, start)) {
+            Ok(((), start + 1))
+        } else {
+            Err(Error::Single(1.0, start))
+        }
+    };
+    let satisfy: &dyn RawParser<Vec<char>, Output = ()> = black_box(&satisfy);
+    let generic = || {
+        let mut end = 0;
+        while let Ok((_, next)) = satisfy.parse(&chars, end) {
+            end = next;
+        }
+        end
+    };
+
+    println!("chars_while: {:?}", measure(fast));
+    println!("per-element: {:?}", measure(generic));
+}
@@ -0,0 +1,63 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use alsein_parsers::parsers::ParserContext;
+
+struct CountingAlloc;
+
+static REALLOCS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        REALLOCS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+const ROUNDS: u32 = 20;
+
+fn measure(f: impl Fn() -> usize) -> (Duration, usize) {
+    let reallocs = REALLOCS.load(Ordering::Relaxed);
+    let now = Instant::now();
+    for _ in 0..ROUNDS {
+        assert_eq!(f(), 1 << 20);
+    }
+    (
+        now.elapsed() / ROUNDS,
+        (REALLOCS.load(Ordering::Relaxed) - reallocs) / ROUNDS as usize,
+    )
+}
+
+fn main() {
+    let chars = "a".repeat(1 << 20).chars().collect::<Vec<_>>();
+    let context = ParserContext::new();
+    let a = context.single('a');
+
+    let growing = a.many_max(usize::MAX);
+    let growing = || growing.parse(&chars).unwrap().len();
+
+    let reserved = a.many0_with_capacity(chars.len());
+    let reserved = || reserved.parse(&chars).unwrap().len();
+
+    let (time, reallocs) = measure(growing);
+    println!("many_max: {:?}, {} reallocations", time, reallocs);
+    let (time, reallocs) = measure(reserved);
+    println!(
+        "many0_with_capacity: {:?}, {} reallocations",
+        time, reallocs
+    );
+}
@@ -0,0 +1,59 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use alsein_parsers::parsers::{Error, ParserContext};
+
+const ROUNDS: u32 = 20;
+
+fn measure<T>(f: impl Fn() -> T) -> Duration {
+    let now = Instant::now();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    now.elapsed() / ROUNDS
+}
+
+fn main() {
+    let chars = format!("{}z", " ".repeat(1 << 12))
+        .chars()
+        .collect::<Vec<_>>();
+    let scans = AtomicUsize::new(0);
+    let context = ParserContext::new();
+    let space = context.new_parser(|input: &Vec<char>, start| {
+        scans.fetch_add(1, Ordering::Relaxed);
+        if start < input.len() && input[start] == ' ' {
+            Ok(((), start + 1))
+        } else {
+            Err(Error::Single(0.0, start))
+        }
+    });
+    context.set_skipper((!space).into_dyn_sync());
+    let spaces = space.many0_progress(|_| ());
+
+    let letters = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'z'];
+    let cached = letters
+        .iter()
+        .map(|&c| context.single(c).token().into_dyn())
+        .reduce(|l, r| (l | r).into_dyn())
+        .unwrap();
+    let uncached = letters
+        .iter()
+        .map(|&c| (!spaces + context.single(c)).into_dyn())
+        .reduce(|l, r| (l | r).into_dyn())
+        .unwrap();
+
+    let time = measure(|| cached.parse(&chars).unwrap());
+    println!(
+        "cached:   {:?} ({} scans)",
+        time,
+        scans.swap(0, Ordering::Relaxed) / ROUNDS as usize
+    );
+    let time = measure(|| uncached.parse(&chars).unwrap());
+    println!(
+        "uncached: {:?} ({} scans)",
+        time,
+        scans.swap(0, Ordering::Relaxed) / ROUNDS as usize
+    );
+}
@@ -1,46 +1,109 @@
 use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
     fmt::{Debug, Display},
     marker::PhantomData,
-    ops::{Add, BitOr, Deref, Not, Range},
+    ops::{Add, BitOr, Deref, Mul, Not, Range, RangeInclusive},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
 };
 
 use crate::pool::Pool;
 
+use self::{cache::ResultCache, skip::SkipCache};
+
+mod anchor;
+mod cache;
+mod caret;
+mod chars;
+mod choice;
+mod convenience;
+mod debug;
+mod deferred;
+mod emit;
+mod grammar;
+mod highlight;
+#[cfg(any(feature = "anyhow", feature = "miette"))]
+mod integration;
+mod lookahead;
+mod mode;
+mod recovery;
+mod repeat;
 mod set;
+mod skip;
+mod span;
+mod suggest;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod tokenizer;
+mod tuple;
+mod verify;
+pub use caret::*;
+pub use choice::*;
+pub use convenience::*;
+pub use debug::*;
+pub use grammar::*;
+pub use recovery::*;
+pub use repeat::*;
 pub use set::*;
+pub use span::*;
+pub use tokenizer::*;
+pub use tuple::*;
 
 type ParserResult<O> = Result<(O, usize)>;
 type Result<O> = std::result::Result<O, Error>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Error {
     Single(f64, usize),
     Add(Vec<Error>),
     Or(Vec<Error>),
     Succeed(Range<usize>),
     Hinted(Box<Error>, String),
+    StaticHint(Box<Error>, &'static str),
+    Fatal(Box<Error>),
+    Incomplete(usize),
+    Trailing(Range<usize>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Soft,
+    Hard,
 }
 
 impl Error {
     pub fn range(&self) -> Range<usize> {
         match self {
-            &Error::Single(_, pos) => pos..pos + 1,
+            &Error::Single(_, pos) | &Error::Incomplete(pos) => pos..pos + 1,
             Error::Add(l) => l[0].range().start..l[l.len() - 1].range().end,
             Error::Or(l) => l
                 .iter()
-                .max_by(|&x, &y| x.similarity().partial_cmp(&y.similarity()).unwrap())
+                .max_by(|&x, &y| {
+                    x.similarity()
+                        .partial_cmp(&y.similarity())
+                        .unwrap()
+                        .then_with(|| x.range().end.cmp(&y.range().end))
+                })
                 .unwrap()
                 .range(),
-            Error::Succeed(range) => range.clone(),
-            Error::Hinted(inner, _) => inner.range(),
+            Error::Succeed(range) | Error::Trailing(range) => range.clone(),
+            Error::Hinted(inner, _) | Error::StaticHint(inner, _) => inner.range(),
+            Error::Fatal(inner) => inner.range(),
         }
     }
 
     pub fn similarity(&self) -> f64 {
         match self {
             &Error::Single(sim, _) => sim,
+            Error::Incomplete(_) | Error::Trailing(_) => 0.0,
             Error::Add(l) => {
-                l.iter().map(Self::similarity).sum::<f64>() / self.range().len() as f64
+                l.iter()
+                    .map(|e| e.similarity() * e.range().len() as f64)
+                    .sum::<f64>()
+                    / self.range().len() as f64
             }
             Error::Or(l) => l
                 .iter()
@@ -48,17 +111,86 @@ impl Error {
                 .max_by(|x, y| x.partial_cmp(y).unwrap())
                 .unwrap(),
             Error::Succeed(_) => 1.0,
-            Error::Hinted(inner, _) => inner.similarity(),
+            Error::Hinted(inner, _) | Error::StaticHint(inner, _) => inner.similarity(),
+            Error::Fatal(inner) => inner.similarity(),
+        }
+    }
+
+    pub fn prune(&self, threshold: f64) -> Error {
+        match self {
+            Error::Or(l) => {
+                let best = l
+                    .iter()
+                    .map(Self::similarity)
+                    .max_by(|x, y| x.partial_cmp(y).unwrap())
+                    .unwrap();
+                let mut l = l
+                    .iter()
+                    .filter(|e| e.similarity() >= threshold.min(best))
+                    .map(|e| e.prune(threshold))
+                    .collect::<Vec<_>>();
+                if l.len() == 1 {
+                    l.remove(0)
+                } else {
+                    Error::Or(l)
+                }
+            }
+            Error::Add(l) => Error::Add(l.iter().map(|e| e.prune(threshold)).collect()),
+            Error::Hinted(inner, s) => Error::Hinted(Box::new(inner.prune(threshold)), s.clone()),
+            Error::StaticHint(inner, s) => Error::StaticHint(Box::new(inner.prune(threshold)), s),
+            Error::Fatal(inner) => Error::Fatal(Box::new(inner.prune(threshold))),
+            Error::Single(..) | Error::Succeed(_) | Error::Incomplete(_) | Error::Trailing(_) => {
+                self.clone()
+            }
+        }
+    }
+
+    pub fn flatten_alternatives(&self) -> Vec<&Error> {
+        match self {
+            Error::Or(l) => l.iter().flat_map(Self::flatten_alternatives).collect(),
+            _ => vec![self],
+        }
+    }
+
+    pub fn offset(self, base: usize) -> Error {
+        match self {
+            Error::Single(sim, pos) => Error::Single(sim, pos + base),
+            Error::Incomplete(pos) => Error::Incomplete(pos + base),
+            Error::Add(l) => Error::Add(l.into_iter().map(|e| e.offset(base)).collect()),
+            Error::Or(l) => Error::Or(l.into_iter().map(|e| e.offset(base)).collect()),
+            Error::Succeed(range) => Error::Succeed(range.start + base..range.end + base),
+            Error::Trailing(range) => Error::Trailing(range.start + base..range.end + base),
+            Error::Hinted(inner, s) => Error::Hinted(Box::new(inner.offset(base)), s),
+            Error::StaticHint(inner, s) => Error::StaticHint(Box::new(inner.offset(base)), s),
+            Error::Fatal(inner) => Error::Fatal(Box::new(inner.offset(base))),
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Fatal(_) => ErrorKind::Hard,
+            Error::Add(l) | Error::Or(l) => {
+                if l.iter().any(|e| e.kind() == ErrorKind::Hard) {
+                    ErrorKind::Hard
+                } else {
+                    ErrorKind::Soft
+                }
+            }
+            Error::Hinted(inner, _) | Error::StaticHint(inner, _) => inner.kind(),
+            Error::Single(..) | Error::Succeed(_) | Error::Incomplete(_) | Error::Trailing(_) => {
+                ErrorKind::Soft
+            }
         }
     }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if let Error::Hinted(_, s) = self {
-            Display::fmt(s, f)
-        } else {
-            Debug::fmt(&self, f)
+        match self {
+            Error::Hinted(_, s) => Display::fmt(s, f),
+            Error::StaticHint(_, s) => Display::fmt(s, f),
+            Error::Fatal(inner) => Display::fmt(inner, f),
+            _ => Debug::fmt(&self, f),
         }
     }
 }
@@ -110,6 +242,9 @@ impl BitOr for Error {
 pub trait RawParser<I: Set + ?Sized> {
     type Output;
     fn parse(&self, input: &I, start: usize) -> ParserResult<Self::Output>;
+    fn resume(&self, error: &Error) -> usize {
+        error.range().end
+    }
 }
 
 impl<I: Set + ?Sized, O, F: Fn(&I, usize) -> ParserResult<O>> RawParser<I> for F {
@@ -127,11 +262,7 @@ pub struct Parser<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized + 'a> {
 
 impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized + 'a> Clone for Parser<'a, I, R> {
     fn clone(&self) -> Self {
-        Parser {
-            raw: self.raw,
-            context: self.context,
-            _phantom: PhantomData,
-        }
+        *self
     }
 }
 
@@ -143,7 +274,7 @@ pub struct Matcher<'a, I: Set + ?Sized, R: RawParser<I, Output = ()> + ?Sized +
 
 impl<'a, I: Set + ?Sized, R: RawParser<I, Output = ()> + ?Sized + 'a> Clone for Matcher<'a, I, R> {
     fn clone(&self) -> Self {
-        Matcher(self.0.clone())
+        *self
     }
 }
 
@@ -158,18 +289,182 @@ impl<'a, I: Set, R: RawParser<I, Output = ()>> Deref for Matcher<'a, I, R> {
 }
 
 pub type ParserDyn<'a, I, O> = Parser<'a, I, dyn RawParser<I, Output = O> + 'a>;
+pub type ParserSyncDyn<'a, I, O> = Parser<'a, I, dyn RawParser<I, Output = O> + Send + Sync + 'a>;
+pub type MatcherDyn<'a, I> = Matcher<'a, I, dyn RawParser<I, Output = ()> + 'a>;
+pub type MatcherSyncDyn<'a, I> = Matcher<'a, I, dyn RawParser<I, Output = ()> + Send + Sync + 'a>;
 
 impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R> {
+    fn run(&self, input: &I, start: usize) -> ParserResult<R::Output> {
+        if self.context.poisoned.load(Ordering::Relaxed) {
+            if let Some(e) = &*self.context.poison.lock().unwrap() {
+                return Err(e.clone());
+            }
+        }
+        self.context.steps.fetch_add(1, Ordering::Relaxed);
+        self.raw.parse(input, start)
+    }
+
+    fn run_top_level(&self, input: &I) -> ParserResult<R::Output> {
+        self.context.skip_cache.lock().unwrap().reset(input);
+        self.context.reset();
+        self.run(input, 0)
+    }
+
     pub fn parse(&self, input: &I) -> Result<<R as RawParser<I>>::Output> {
-        Ok(self.raw.parse(&input, 0)?.0)
+        Ok(self.run_top_level(input)?.0)
+    }
+
+    pub fn parse_all(&self, input: &I) -> Result<<R as RawParser<I>>::Output> {
+        match self.run_top_level(input)? {
+            (result, end) if end >= input.len() => Ok(result),
+            (_, end) => Err(Error::Trailing(end..input.len())),
+        }
     }
 
     pub fn map<T>(
         self,
-        f: impl Fn(<R as RawParser<I>>::Output) -> T,
+        f: impl Fn(<R as RawParser<I>>::Output) -> T + 'a,
+    ) -> Parser<'a, I, impl RawParser<I, Output = T> + 'a> {
+        self.context
+            .new_parser(move |input: &I, start| self.run(input, start).map(|(v, end)| (f(v), end)))
+    }
+
+    pub fn map_or<T: Clone + 'a>(
+        self,
+        default: T,
+        f: impl Fn(<R as RawParser<I>>::Output) -> T + 'a,
+    ) -> Parser<'a, I, impl RawParser<I, Output = T> + 'a> {
+        self.context
+            .new_parser(move |input: &I, start| match self.run(input, start) {
+                Ok((v, end)) => Ok((f(v), end)),
+                Err(e) if e.kind() == ErrorKind::Hard => Err(e),
+                Err(_) => Ok((default.clone(), start)),
+            })
+    }
+
+    pub fn map_end<T>(
+        self,
+        f: impl Fn(<R as RawParser<I>>::Output, usize) -> T + 'a,
+    ) -> Parser<'a, I, impl RawParser<I, Output = T> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            self.run(input, start).map(|(v, end)| (f(v, end), end))
+        })
+    }
+
+    pub fn label(self, name: &str) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        let name = name.to_string();
+        self.context.new_parser(move |input: &I, start| {
+            self.run(input, start).map_err(|e| {
+                self.context.record_hint(&e, &name);
+                Error::Hinted(Box::new(e), name.clone())
+            })
+        })
+    }
+
+    pub fn map_err(
+        self,
+        f: impl Fn(Error) -> Error + 'a,
+    ) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.context
+            .new_parser(move |input: &I, start| self.run(input, start).map_err(&f))
+    }
+
+    pub fn try_map<T>(
+        self,
+        f: impl Fn(R::Output, Range<usize>) -> Result<T> + 'a,
     ) -> Parser<'a, I, impl RawParser<I, Output = T> + 'a> {
         self.context.new_parser(move |input: &I, start| {
-            self.raw.parse(input, start).map(|(v, end)| (f(v), end))
+            let (result, end) = self.run(input, start)?;
+            Ok((f(result, start..end)?, end))
+        })
+    }
+
+    pub fn expected(self, what: &str) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        let hint = format!("expected {}", what);
+        self.map_err(move |e| Error::Hinted(Box::new(e), hint.clone()))
+    }
+
+    pub fn cut(self) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            self.run(input, start).map_err(|e| match e {
+                Error::Fatal(_) => e,
+                e => Error::Fatal(Box::new(e)),
+            })
+        })
+    }
+
+    pub fn fatal(self) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        let cut = self.cut();
+        self.context.new_parser(move |input: &I, start| {
+            cut.run(input, start).inspect_err(|e| {
+                *self.context.poison.lock().unwrap() = Some(e.clone());
+                self.context.poisoned.store(true, Ordering::Relaxed);
+            })
+        })
+    }
+
+    pub fn many0(self) -> Parser<'a, I, impl RawParser<I, Output = Vec<R::Output>> + 'a> {
+        self.many0_with_capacity(0)
+    }
+
+    pub fn many1(self) -> Parser<'a, I, impl RawParser<I, Output = Vec<R::Output>> + 'a> {
+        let rest = self.many0();
+        self.context.new_parser(move |input: &I, start| {
+            let (first, end) = self.run(input, start)?;
+            let (mut results, end) = rest.run(input, end)?;
+            results.insert(0, first);
+            Ok((results, end))
+        })
+    }
+
+    pub fn opt(self) -> Parser<'a, I, impl RawParser<I, Output = Option<R::Output>> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            let snapshot = self.context.snapshot();
+            match self.run(input, start) {
+                Ok((result, end)) => Ok((Some(result), end)),
+                Err(e) if e.kind() == ErrorKind::Hard => Err(e),
+                Err(_) => {
+                    self.context.restore(snapshot);
+                    Ok((None, start))
+                }
+            }
+        })
+    }
+
+    pub fn or_soft<R2: RawParser<I, Output = R::Output> + ?Sized + 'a>(
+        self,
+        other: Parser<'a, I, R2>,
+    ) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            let snapshot = self.context.snapshot();
+            match self.run(input, start) {
+                Ok(r) => Ok(r),
+                Err(e1) if e1.kind() == ErrorKind::Hard => Err(e1),
+                Err(e1) => {
+                    self.context.restore(snapshot);
+                    match other.run(input, start) {
+                        Ok(r) => Ok(r),
+                        Err(e2) => Err(e1 | e2),
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl<'a, T: 'static, R: RawParser<[T]> + ?Sized> Parser<'a, [T], R> {
+    pub fn parse_partial<'i>(&self, input: &'i [T]) -> Result<(R::Output, &'i [T])> {
+        let (result, end) = self.run_top_level(input)?;
+        Ok((result, &input[end..]))
+    }
+
+    pub fn within(
+        self,
+        max_len: usize,
+    ) -> Parser<'a, [T], impl RawParser<[T], Output = R::Output> + 'a> {
+        self.context.new_parser(move |input: &[T], start: usize| {
+            let end = start.saturating_add(max_len).min(input.len());
+            self.run(&input[..end], start)
         })
     }
 }
@@ -188,7 +483,106 @@ impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Not for Parser<'a, I, R> {
 impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized + 'a> RawParser<I> for Discard<'a, I, R> {
     type Output = ();
     fn parse(&self, input: &I, start: usize) -> ParserResult<Self::Output> {
-        self.0.raw.parse(input, start).map(|(_, end)| ((), end))
+        self.0.run(input, start).map(|(_, end)| ((), end))
+    }
+    fn resume(&self, error: &Error) -> usize {
+        self.0.raw.resume(error)
+    }
+}
+
+pub struct Repeat<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized + 'a>(
+    Parser<'a, I, R>,
+    usize,
+    usize,
+);
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Mul<Range<usize>> for Parser<'a, I, R> {
+    type Output = Parser<'a, I, Repeat<'a, I, R>>;
+
+    fn mul(self, rhs: Range<usize>) -> Self::Output {
+        self.context
+            .new_parser(Repeat(self, rhs.start, rhs.end.saturating_sub(1)))
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Mul<RangeInclusive<usize>>
+    for Parser<'a, I, R>
+{
+    type Output = Parser<'a, I, Repeat<'a, I, R>>;
+
+    fn mul(self, rhs: RangeInclusive<usize>) -> Self::Output {
+        self.context
+            .new_parser(Repeat(self, *rhs.start(), *rhs.end()))
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Mul<usize> for Parser<'a, I, R> {
+    type Output = Parser<'a, I, Repeat<'a, I, R>>;
+
+    fn mul(self, rhs: usize) -> Self::Output {
+        self.context.new_parser(Repeat(self, rhs, rhs))
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized + 'a> RawParser<I> for Repeat<'a, I, R> {
+    type Output = Vec<R::Output>;
+    fn parse(&self, input: &I, start: usize) -> ParserResult<Self::Output> {
+        let Repeat(parser, min, max) = self;
+        let mut results = Vec::new();
+        let mut pos = start;
+        while results.len() < *max {
+            let snapshot = parser.context.snapshot();
+            match parser.run(input, pos) {
+                Ok((result, end)) => {
+                    results.push(result);
+                    if end == pos && results.len() >= *min {
+                        break;
+                    }
+                    pos = end;
+                }
+                Err(e) if results.len() < *min || e.kind() == ErrorKind::Hard => {
+                    return Err(if pos > start {
+                        Error::Succeed(start..pos) + e
+                    } else {
+                        e
+                    });
+                }
+                Err(_) => {
+                    parser.context.restore(snapshot);
+                    break;
+                }
+            }
+        }
+        if results.len() < *min {
+            return Err(Error::Succeed(start..pos) + Error::Single(0.0, pos));
+        }
+        Ok((results, pos))
+    }
+}
+
+pub struct ResumeAt<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized + 'a, F: Fn(&Error) -> usize>(
+    Parser<'a, I, R>,
+    F,
+);
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R> {
+    pub fn resume_at<F: Fn(&Error) -> usize + 'a>(
+        self,
+        f: F,
+    ) -> Parser<'a, I, ResumeAt<'a, I, R, F>> {
+        self.context.new_parser(ResumeAt(self, f))
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized + 'a, F: Fn(&Error) -> usize> RawParser<I>
+    for ResumeAt<'a, I, R, F>
+{
+    type Output = R::Output;
+    fn parse(&self, input: &I, start: usize) -> ParserResult<Self::Output> {
+        self.0.run(input, start)
+    }
+    fn resume(&self, error: &Error) -> usize {
+        (self.1)(error).max(error.range().start)
     }
 }
 
@@ -202,6 +596,28 @@ impl<'a, O, I: Set, R: RawParser<I, Output = O>> Parser<'a, I, R> {
     }
 }
 
+impl<'a, I: Set, R: RawParser<I, Output = ()>> Matcher<'a, I, R> {
+    pub fn into_dyn(self) -> MatcherDyn<'a, I> {
+        Matcher(self.0.into_dyn())
+    }
+}
+
+impl<'a, O, I: Set, R: RawParser<I, Output = O> + Send + Sync> Parser<'a, I, R> {
+    pub fn into_dyn_sync(self) -> ParserSyncDyn<'a, I, O> {
+        Parser {
+            raw: self.raw,
+            context: self.context,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, I: Set, R: RawParser<I, Output = ()> + Send + Sync> Matcher<'a, I, R> {
+    pub fn into_dyn_sync(self) -> MatcherSyncDyn<'a, I> {
+        Matcher(self.0.into_dyn_sync())
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct Or<
     'a,
@@ -236,12 +652,17 @@ impl<
 {
     type Output = O;
     fn parse(&self, input: &I, start: usize) -> ParserResult<Self::Output> {
-        match self.0.raw.parse(input, start) {
+        let snapshot = self.0.context.snapshot();
+        match self.0.run(input, start) {
             Ok(r) => Ok(r),
-            Err(e1) => match self.1.raw.parse(input, start) {
-                Ok(r) => Ok(r),
-                Err(e2) => Err(e1 | e2),
-            },
+            Err(e1) if e1.kind() == ErrorKind::Hard => Err(e1),
+            Err(e1) => {
+                self.0.context.restore(snapshot);
+                match self.1.run(input, start) {
+                    Ok(r) => Ok(r),
+                    Err(e2) => Err(e1 | e2),
+                }
+            }
         }
     }
 }
@@ -268,14 +689,17 @@ impl<'a, I: Set + ?Sized, R1: RawParser<I> + ?Sized + 'a, R2: RawParser<I> + ?Si
 {
     type Output = (R1::Output, R2::Output);
     fn parse(&self, input: &I, start: usize) -> ParserResult<Self::Output> {
-        match self.0.raw.parse(input, start) {
-            Ok((r1, end1)) => match self.1.raw.parse(input, end1) {
+        match self.0.run(input, start) {
+            Ok((r1, end1)) => match self.1.run(input, end1) {
                 Ok((r2, end2)) => Ok(((r1, r2), end2)),
                 Err(e2) => Err(Error::Succeed(start..end1) + e2),
             },
             Err(e1) => {
-                let start = e1.range().end;
-                match self.1.raw.parse(input, start) {
+                let start = self.0.raw.resume(&e1);
+                if start > input.len() {
+                    return Err(e1);
+                }
+                match self.1.run(input, start) {
                     Ok((_, end)) => Err(e1 + Error::Succeed(start..end)),
                     Err(e2) => Err(e1 + e2),
                 }
@@ -357,13 +781,67 @@ impl<
             .map(|((_, result), end)| (result, end))
     }
 }
+enum ModeChange {
+    Pushed,
+    Popped(&'static str),
+}
+
+#[derive(Default)]
+struct Diagnostics<'a> {
+    errors: Vec<Error>,
+    warnings: Vec<String>,
+    modes: Vec<&'static str>,
+    mode_changes: Vec<ModeChange>,
+    tokens: Vec<(String, Range<usize>, usize)>,
+    notes: Vec<(&'static str, usize)>,
+    emitted: Vec<(TypeId, Box<dyn Any + Send>)>,
+    deferred: Vec<(usize, Box<dyn FnOnce() + Send + 'a>)>,
+}
+
+type Slot<'a> = Mutex<Option<(TypeId, &'a (dyn Send + Sync + 'a))>>;
+
 pub struct ParserContext<'a> {
+    diagnostics: &'a Mutex<Diagnostics<'a>>,
     pool: Pool<'a>,
+    poisoned: AtomicBool,
+    poison: Mutex<Option<Error>>,
+    profiles: Mutex<HashMap<String, ProfileStats>>,
+    skipper: Slot<'a>,
+    skip_cache: Mutex<SkipCache>,
+    sync_tokens: Slot<'a>,
+    token_depth: AtomicUsize,
+    result_cache: Mutex<ResultCache>,
+    steps: AtomicUsize,
+    suggest_at: Mutex<Option<(usize, Vec<String>)>>,
+    skipped: Mutex<Option<Vec<Range<usize>>>>,
+}
+
+impl ParserContext<'static> {
+    /// Leaks the context so grammars built from it are `'static`; its pool is never freed.
+    /// Per-run state still lives on the context, so parse from one thread at a time.
+    pub fn into_static(self) -> &'static Self {
+        Box::leak(Box::new(self))
+    }
 }
 
 impl Default for ParserContext<'_> {
     fn default() -> Self {
-        Self { pool: Pool::new() }
+        let pool = Pool::new();
+        Self {
+            diagnostics: pool.add(Box::default()),
+            pool,
+            poisoned: Default::default(),
+            poison: Default::default(),
+            profiles: Default::default(),
+            skipper: Default::default(),
+            skip_cache: Default::default(),
+            sync_tokens: Default::default(),
+            token_depth: Default::default(),
+            result_cache: Default::default(),
+            steps: Default::default(),
+            suggest_at: Default::default(),
+            skipped: Default::default(),
+        }
     }
 }
 
@@ -380,15 +858,95 @@ impl<'a> ParserContext<'a> {
         }
     }
 
-    pub fn single<E: PartialEq + Clone, I: Set<Output = E>>(
-        &self,
+    fn reset(&self) {
+        self.clear_poison();
+        {
+            let mut diagnostics = self.diagnostics.lock().unwrap();
+            diagnostics.modes.clear();
+            diagnostics.mode_changes.clear();
+            diagnostics.tokens.clear();
+            diagnostics.notes.clear();
+        }
+        self.token_depth.store(0, Ordering::Relaxed);
+        self.steps.store(0, Ordering::Relaxed);
+    }
+
+    fn clear_poison(&self) {
+        *self.poison.lock().unwrap() = None;
+        self.poisoned.store(false, Ordering::Relaxed);
+    }
+
+    pub fn single<E: PartialEq + Clone + Debug + 'a, I: Set<Output = E> + ?Sized>(
+        &'a self,
         value: E,
-    ) -> Parser<I, impl RawParser<I, Output = E>> {
+    ) -> Parser<'a, I, impl RawParser<I, Output = E>> {
         self.new_parser(move |input: &I, start| {
-            if &value == input.get(start) {
+            if start >= input.len() {
+                Err(Error::Hinted(
+                    Box::new(Error::Single(1.0, start)),
+                    format!("expected {:?}, found end of input", value),
+                ))
+            } else if &value == input.get(start) {
                 Ok((value.clone(), start + 1))
             } else {
-                Err(Error::Single(1.0, start))
+                Err(Error::Hinted(
+                    Box::new(Error::Single(1.0, start)),
+                    format!("expected {:?}, found {:?}", value, input.get(start)),
+                ))
+            }
+        })
+    }
+
+    pub fn one_of_tokens<E: PartialEq + Clone + Debug + 'a, I: Set<Output = E> + ?Sized>(
+        &'a self,
+        tokens: &[E],
+    ) -> Parser<'a, I, impl RawParser<I, Output = E>> {
+        let tokens = tokens.to_vec();
+        self.new_parser(move |input: &I, start| {
+            if start < input.len() && tokens.contains(input.get(start)) {
+                Ok((input.get(start).clone(), start + 1))
+            } else {
+                Err(Error::Hinted(
+                    Box::new(Error::Single(0.0, start)),
+                    format!("expected one of {:?}", tokens),
+                ))
+            }
+        })
+    }
+
+    pub fn tag<E: PartialEq + Clone + Debug + 'a, I: Set<Output = E> + ?Sized>(
+        &'a self,
+        value: Vec<E>,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Vec<E>>> {
+        self.tag_with(value, |elements| format!("{:?}", elements))
+    }
+
+    fn tag_with<E: PartialEq + Clone + 'a, I: Set<Output = E> + ?Sized>(
+        &'a self,
+        value: Vec<E>,
+        describe: fn(&[E]) -> String,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Vec<E>>> {
+        self.new_parser(move |input: &I, start| {
+            match value
+                .iter()
+                .enumerate()
+                .find(|&(i, v)| start + i >= input.len() || v != input.get(start + i))
+            {
+                None => Ok((value.clone(), start + value.len())),
+                Some((i, _)) => {
+                    let found = (start..input.len().min(start + value.len()))
+                        .map(|pos| input.get(pos).clone())
+                        .collect::<Vec<_>>();
+                    Err(Error::Hinted(
+                        Box::new(Error::Single(i as f64 / value.len() as f64, start + i)),
+                        format!(
+                            "expected {}, found {} (at offset {})",
+                            describe(&value),
+                            describe(&found),
+                            i
+                        ),
+                    ))
+                }
             }
         })
     }
@@ -407,8 +965,8 @@ mod test {
         _phantom: PhantomData<I>,
     }
 
-    impl<I: Set<Output = char>> TestParser<'_, I> {
-        fn a(&self) -> Parser<I, impl RawParser<I, Output = char>> {
+    impl<'a, I: Set<Output = char>> TestParser<'a, I> {
+        fn a(&'a self) -> Parser<'a, I, impl RawParser<I, Output = char>> {
             self.context.single('a')
         }
     }
@@ -426,3 +984,379 @@ mod test {
         let x = c.parse(&chars).unwrap();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn context_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<ParserContext<'static>>();
+    }
+
+    #[test]
+    fn wrappers_respect_poisoning() {
+        let chars = "b".chars().collect::<Vec<_>>();
+        let calls = std::cell::Cell::new(0);
+        let context = ParserContext::new();
+        let counted = context.new_parser(|_: &Vec<char>, start| {
+            calls.set(calls.get() + 1);
+            Ok(('b', start + 1))
+        });
+        let fatal = context.single('a').fatal();
+        assert!((fatal + counted.label("counted")).parse(&chars).is_err());
+        assert!((fatal + counted.map_end(|c, _| c)).parse(&chars).is_err());
+        assert_eq!(calls.get(), 0);
+        assert_eq!(
+            context
+                .single('a')
+                .map_end(|_, end| end)
+                .timed(&chars)
+                .1
+                .steps,
+            2
+        );
+    }
+
+    #[test]
+    fn or_soft_propagates_hard_errors() {
+        let chars = "b".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let soft = context.single('a').or_soft(context.single('b'));
+        assert_eq!(soft.parse(&chars).unwrap(), 'b');
+        let hard = context.single('a').fatal().or_soft(context.single('b'));
+        assert_eq!(hard.parse(&chars).unwrap_err().kind(), ErrorKind::Hard);
+    }
+
+    #[test]
+    fn fallible_wrappers_propagate_hard_errors() {
+        let chars = "ac".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let cut = context.single('a') + context.single('b').cut();
+        let a = context.single('a');
+        let hard = |e: Option<Error>| e.is_some_and(|e| e.kind() == ErrorKind::Hard);
+        assert!(hard(cut.map_or(' ', |(a, _)| a).parse(&chars).err()));
+        assert!(hard(cut.opt_tracked("pair").parse(&chars).err()));
+        assert!(hard(cut.skip_until(!a).parse(&chars).err()));
+        assert!(hard(cut.or_else_ctx(move |_| a + a).parse(&chars).err()));
+        let chain = context.fallback_chain(vec![cut.into_dyn(), (a + a).into_dyn()]);
+        assert!(hard(chain.parse(&chars).err()));
+        assert!(hard(cut.repeat_diagnosed(2).parse(&chars).err()));
+    }
+
+    #[test]
+    fn cut_stops_alternatives_after_prefix() {
+        let chars = "let 1".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let name = context
+            .chars_while(char::is_alphabetic)
+            .map(|r| r.len())
+            .require_progress();
+        let binding = !context.str_tag("let ") + name.cut();
+        let expression = context.chars_while(|_| true).map(|r| r.len());
+        let statement = binding | expression;
+        let error = statement.parse(&chars).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Hard);
+        assert!(!matches!(error, Error::Or(_)));
+        let uncut = (!context.str_tag("let ") + name) | expression;
+        assert_eq!(uncut.parse(&chars).unwrap(), 5);
+        let chars = "let x".chars().collect::<Vec<_>>();
+        assert_eq!(statement.parse(&chars).unwrap(), 1);
+    }
+
+    #[test]
+    fn map_err_rewrites_inner_errors() {
+        let chars = "b".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let hard = context.single('a').map_err(|e| Error::Fatal(Box::new(e)));
+        assert_eq!(
+            (hard | context.single('b'))
+                .parse(&chars)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::Hard
+        );
+        let soft = hard.map_err(|e| match e {
+            Error::Fatal(inner) => *inner,
+            e => e,
+        });
+        assert_eq!((soft | context.single('b')).parse(&chars).unwrap(), 'b');
+    }
+
+    #[test]
+    fn try_map_fails_at_span() {
+        let context = ParserContext::new();
+        let byte = context
+            .chars_while(|c| c.is_ascii_digit())
+            .text()
+            .try_map(|digits, span| {
+                digits
+                    .parse::<u8>()
+                    .map_err(|e| Error::Hinted(Box::new(Error::Succeed(span)), e.to_string()))
+            });
+        let values = byte.separated_by(!context.single(','));
+        let chars = "12,255".chars().collect::<Vec<_>>();
+        assert_eq!(values.parse(&chars).unwrap(), vec![12, 255]);
+        let chars = "300".chars().collect::<Vec<_>>();
+        let error = byte.parse(&chars).unwrap_err();
+        assert_eq!(error.range(), 0..3);
+        assert_eq!(error.to_string(), "number too large to fit in target type");
+    }
+
+    #[test]
+    fn expected_names_grammar_concept() {
+        let chars = "x".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let integer = context
+            .chars_while(|c| c.is_ascii_digit())
+            .require_progress()
+            .expected("an integer literal");
+        let error = integer.parse(&chars).unwrap_err();
+        assert!(matches!(error, Error::Hinted(..)));
+        assert_eq!(error.to_string(), "expected an integer literal");
+    }
+
+    #[test]
+    fn prune_drops_dissimilar_alternatives() {
+        let error = [0.9, 0.2, 0.6, 0.4, 0.1]
+            .iter()
+            .enumerate()
+            .map(|(pos, &sim)| Error::Single(sim, pos))
+            .fold(Error::Or(vec![]), |l, e| l | e);
+        match error.prune(0.5) {
+            Error::Or(l) => assert_eq!(
+                l.iter().map(Error::similarity).collect::<Vec<_>>(),
+                vec![0.9, 0.6]
+            ),
+            e => panic!("unexpected {:?}", e),
+        }
+        assert_eq!(error.prune(0.95).similarity(), 0.9);
+    }
+
+    #[test]
+    fn map_end_receives_end_offset() {
+        let chars = "let x".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let keyword = context.tag("let".chars().collect()).map_end(|_, end| end);
+        assert_eq!(keyword.parse(&chars).unwrap(), 3);
+    }
+
+    #[test]
+    fn parse_partial_returns_remainder() {
+        let chars = "ab!!".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let ab = context.single('a') + context.single('b');
+        let (result, rest) = ab.parse_partial(&chars).unwrap();
+        assert_eq!(result, ('a', 'b'));
+        assert_eq!(rest, &['!', '!']);
+    }
+
+    #[test]
+    fn map_or_defaults_when_absent() {
+        let context = ParserContext::new();
+        let sign =
+            (context.single('-') | context.single('+'))
+                .map_or(1, |c| if c == '-' { -1 } else { 1 });
+        let number = sign + !context.single('5');
+        let parse = |s: &str| number.parse(&s.chars().collect::<Vec<_>>()).unwrap();
+        assert_eq!(parse("-5"), -1);
+        assert_eq!(parse("+5"), 1);
+        assert_eq!(parse("5"), 1);
+    }
+
+    #[test]
+    fn fatal_error_short_circuits_later_parsers() {
+        let chars = "b".chars().collect::<Vec<_>>();
+        let calls = std::cell::Cell::new(0);
+        let context = ParserContext::new();
+        let counted = context.new_parser(|_: &Vec<char>, start| {
+            calls.set(calls.get() + 1);
+            Ok(('b', start + 1))
+        });
+        let soft = context.single('a') | counted;
+        assert_eq!(soft.parse(&chars).unwrap(), 'b');
+        assert_eq!(calls.get(), 1);
+        let hard = context.single('a').fatal() | counted;
+        assert_eq!(hard.parse(&chars).unwrap_err().kind(), ErrorKind::Hard);
+        assert_eq!(calls.get(), 1);
+        assert_eq!(soft.parse(&chars).unwrap(), 'b');
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn one_of_tokens_reports_flat_error() {
+        let context = ParserContext::new();
+        let operator = context.one_of_tokens(&['+', '-', '*']);
+        assert_eq!(operator.parse(&vec!['-']).unwrap(), '-');
+        let error = operator.parse(&vec!['/']).unwrap_err();
+        assert_eq!(error.to_string(), "expected one of ['+', '-', '*']");
+        assert_eq!(error.range(), 0..1);
+    }
+
+    #[test]
+    fn resume_at_overrides_sequence_recovery() {
+        let chars = "xb".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let default = context.single('a') + context.single('b');
+        assert_eq!(default.parse(&chars).unwrap_err().range(), 0..2);
+        let custom = context.single('a').resume_at(|e| e.range().start) + context.single('b');
+        assert_eq!(custom.parse(&chars).unwrap_err().range(), 0..1);
+        let chars = "abz".chars().collect::<Vec<_>>();
+        let early = context.str_tag("abc").resume_at(|_| 0) + context.single('z');
+        assert_eq!(early.parse(&chars).unwrap_err().range(), 2..3);
+    }
+
+    #[test]
+    fn within_caps_greedy_parser() {
+        let chars = ['a'; 5];
+        let context = ParserContext::new();
+        let capped = context.single('a').many0_progress(|_| ()).within(3);
+        let (result, rest) = capped.parse_partial(&chars).unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!(rest.len(), 2);
+        let unbounded = !context.single('a') + context.single('a').many0().within(usize::MAX);
+        assert_eq!(unbounded.parse(&chars).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn tag_error_pinpoints_mismatch() {
+        let context = ParserContext::new();
+        let error = context
+            .tag(vec![1u8, 2, 3])
+            .parse(&vec![1, 2, 4])
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "expected [1, 2, 3], found [1, 2, 4] (at offset 2)"
+        );
+        assert_eq!(error.range(), 2..3);
+    }
+
+    #[test]
+    fn or_prefers_longer_partial_match() {
+        let partial = |len| Error::Succeed(0..len) + Error::Single(0.0, len);
+        assert!(partial(2).similarity() > partial(1).similarity());
+        assert_eq!((partial(1) | partial(2)).range(), 0..3);
+        assert_eq!((partial(2) | partial(1)).range(), 0..3);
+    }
+
+    #[test]
+    fn static_hint_behaves_like_hinted() {
+        let error = Error::StaticHint(Box::new(Error::Single(0.5, 3)), "expected digit");
+        assert_eq!(error.to_string(), "expected digit");
+        assert_eq!(error.range(), 3..4);
+        assert_eq!(error.similarity(), 0.5);
+        assert_eq!(error.offset(2).range(), 5..6);
+    }
+
+    #[test]
+    fn single_error_names_expected_token() {
+        let context = ParserContext::new();
+        let x = context.single('x');
+        let chars = "y".chars().collect::<Vec<_>>();
+        assert_eq!(
+            x.parse(&chars).unwrap_err().to_string(),
+            "expected 'x', found 'y'"
+        );
+        let chars = Vec::new();
+        assert_eq!(
+            x.parse(&chars).unwrap_err().to_string(),
+            "expected 'x', found end of input"
+        );
+    }
+
+    #[test]
+    fn flatten_alternatives_collects_nested_leaves() {
+        let chars = "d".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let [a, b, c] = ['a', 'b', 'c'].map(|t| context.single(t));
+        let choice = a | (b | c);
+        let error = choice.parse(&chars).unwrap_err();
+        let leaves = error
+            .flatten_alternatives()
+            .into_iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            leaves,
+            vec![
+                "expected 'a', found 'd'",
+                "expected 'b', found 'd'",
+                "expected 'c', found 'd'"
+            ]
+        );
+    }
+
+    #[test]
+    fn leaked_context_builds_static_grammars() {
+        static GRAMMAR: std::sync::OnceLock<ParserSyncDyn<'static, Vec<char>, char>> =
+            std::sync::OnceLock::new();
+        let parser = GRAMMAR.get_or_init(|| {
+            let context = ParserContext::new().into_static();
+            (context.single('a') | context.single('b')).into_dyn_sync()
+        });
+        assert_eq!(GRAMMAR.get().unwrap().parse(&vec!['a']).unwrap(), 'a');
+        assert_eq!(parser.parse(&vec!['b']).unwrap(), 'b');
+    }
+
+    #[test]
+    fn many0_collects_until_failure() {
+        let chars = "aab".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let a = context.single('a');
+        assert_eq!(
+            a.many0().parse_partial(&chars).unwrap(),
+            (vec!['a', 'a'], &['b'][..])
+        );
+        assert!(a.many0().parse(&['b'][..]).unwrap().is_empty());
+        let error = (a.many0() + context.single('c')).parse(&chars).unwrap_err();
+        assert_eq!(error.range(), 0..3);
+    }
+
+    #[test]
+    fn many1_requires_first_item() {
+        let chars = "aab".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let a = context.single('a');
+        assert_eq!(a.many1().parse(&chars[..]).unwrap(), vec!['a', 'a']);
+        let error = a.many1().parse(&chars[2..]).unwrap_err();
+        assert_eq!(error.to_string(), "expected 'a', found 'b'");
+    }
+
+    #[test]
+    fn mul_bounds_repetitions() {
+        let chars = "aaaab".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let a = context.single('a');
+        assert_eq!((a * (2..4)).parse_partial(&chars).unwrap().0.len(), 3);
+        assert_eq!((a * (2..=4)).parse_partial(&chars).unwrap().0.len(), 4);
+        assert_eq!((a * 2).parse_partial(&chars).unwrap().1.len(), 3);
+        let error = (a * 5).parse(&chars[..]).unwrap_err();
+        assert!(matches!(&error, Error::Add(l) if l.len() == 2));
+        assert_eq!(error.range(), 0..5);
+    }
+
+    #[test]
+    fn mul_accepts_zero_width_items() {
+        let chars = "ab".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let a = context.single('a').opt() * 3;
+        assert_eq!(a.parse(&chars[1..]).unwrap(), vec![None, None, None]);
+        assert_eq!(
+            a.parse_partial(&chars).unwrap().0,
+            vec![Some('a'), None, None]
+        );
+        let any = context.single('a').opt() * (0..=5);
+        assert_eq!(any.parse(&chars[1..]).unwrap(), vec![None]);
+    }
+
+    #[test]
+    fn opt_turns_failure_into_none() {
+        let chars = "-1".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let number = context.single('-').opt() + context.single('1');
+        assert_eq!(number.parse(&chars[..]).unwrap(), (Some('-'), '1'));
+        assert_eq!(number.parse(&chars[1..]).unwrap(), (None, '1'));
+    }
+}
@@ -0,0 +1,136 @@
+use super::{Error, ErrorKind, Matcher, Parser, ParserContext, RawParser, Set};
+
+impl<'a> ParserContext<'a> {
+    pub fn marker<I: Set + ?Sized>(&'a self) -> Parser<'a, I, impl RawParser<I, Output = usize>> {
+        self.new_parser(|_: &I, start| Ok((start, start)))
+    }
+
+    pub fn end<I: Set + ?Sized>(&'a self) -> Matcher<'a, I, impl RawParser<I, Output = ()>> {
+        Matcher(self.new_parser(|input: &I, start| {
+            if start >= input.len() {
+                Ok(((), start))
+            } else {
+                Err(Error::Trailing(start..input.len()))
+            }
+        }))
+    }
+
+    pub fn at_line_start<I: Set<Output = char> + ?Sized>(
+        &'a self,
+    ) -> Matcher<'a, I, impl RawParser<I, Output = ()>> {
+        Matcher(self.new_parser(|input: &I, start| {
+            if start == 0 || (start <= input.len() && *input.get(start - 1) == '\n') {
+                Ok(((), start))
+            } else {
+                Err(Error::Single(0.0, start))
+            }
+        }))
+    }
+
+    pub fn suffix<I: Set + ?Sized, R: RawParser<I> + ?Sized + 'a>(
+        &'a self,
+        parser: Parser<'a, I, R>,
+        max_len: usize,
+    ) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.new_parser(move |input: &I, start: usize| {
+            let end = input.len();
+            let mut furthest: Option<Error> = None;
+            for pos in (start.max(end.saturating_sub(max_len))..=end).rev() {
+                let snapshot = self.snapshot();
+                let error = match parser.run(input, pos) {
+                    Ok((result, e)) if e == end => return Ok((result, end)),
+                    Ok((_, e)) => Error::Trailing(e..end),
+                    Err(e) if e.kind() == ErrorKind::Hard => return Err(e),
+                    Err(e) => e,
+                };
+                self.restore(snapshot);
+                let rank = |e: &Error| (e.range().end, e.similarity());
+                if furthest.as_ref().is_none_or(|f| rank(f) < rank(&error)) {
+                    furthest = Some(error);
+                }
+            }
+            Err(furthest.unwrap_or(Error::Single(0.0, end)))
+        })
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R>
+where
+    R::Output: Clone + 'a,
+{
+    pub fn or_empty(
+        self,
+        value: R::Output,
+    ) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            if input.is_empty() {
+                Ok((value.clone(), start))
+            } else {
+                self.run(input, start)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::*;
+
+    #[test]
+    fn suffix_matches_footer() {
+        let context = ParserContext::new();
+        let footer = context.suffix(context.tag(vec![0xde, 0xad, 0xbe, 0xef]), 4);
+        let buffer = vec![0xde, 0xad, 0xbe, 0xef, 1, 2, 0xde, 0xad, 0xbe, 0xef];
+        assert_eq!(footer.parse(&buffer).unwrap(), vec![0xde, 0xad, 0xbe, 0xef]);
+        let buffer: Vec<u8> = vec![0xde, 0xad, 0xbe, 0xef, 1, 2, 0xde, 0xad, 0xbe];
+        assert_eq!(
+            footer.parse(&buffer).unwrap_err().to_string(),
+            "expected [222, 173, 190, 239], found [222, 173, 190] (at offset 3)"
+        );
+        let cut = context.suffix(context.tag(vec![1u8]).cut(), 4);
+        assert_eq!(cut.parse(&buffer).unwrap_err().kind(), ErrorKind::Hard);
+    }
+
+    #[test]
+    fn or_empty_supplies_default() {
+        let context = ParserContext::new();
+        let document = context.tag(vec!['o', 'k']).or_empty(vec![]);
+        let empty: &[char] = &[];
+        assert_eq!(document.parse(empty).unwrap(), vec![]);
+        assert_eq!(document.parse(&['o', 'k'][..]).unwrap(), vec!['o', 'k']);
+        assert!(document.parse(&['n', 'o'][..]).is_err());
+    }
+
+    #[test]
+    fn marker_captures_junction() {
+        let chars = "ab".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let joined = context.single('a') + context.marker() + context.single('b');
+        assert_eq!(joined.parse(&chars).unwrap(), (('a', 1), 'b'));
+    }
+
+    #[test]
+    fn at_line_start_requires_preceding_newline() {
+        let chars = "a\n#b#".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let header = context.at_line_start() + context.single('#');
+        let after_newline = !context.str_tag("a\n") + header;
+        assert_eq!(after_newline.parse(&chars).unwrap(), '#');
+        let mid_line = !context.str_tag("a\n#b") + header;
+        assert!(mid_line.parse(&chars).is_err());
+    }
+
+    #[test]
+    fn end_and_parse_all_reject_trailing_input() {
+        let chars = "ab".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let a = context.single('a');
+        assert_eq!(a.parse(&chars).unwrap(), 'a');
+        assert!(matches!(a.parse_all(&chars), Err(Error::Trailing(r)) if r == (1..2)));
+        assert!((a + context.end()).parse(&chars).is_err());
+        assert_eq!(
+            (a + context.end()).parse(&chars[..1].to_vec()).unwrap(),
+            'a'
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use std::{
+    any::Any,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use super::{Parser, ParserContext, RawParser, Result, Set};
+
+#[derive(Default)]
+pub(crate) struct ResultCache {
+    capacity: usize,
+    entries: Vec<((usize, u64), Box<dyn Any + Send>)>,
+}
+
+impl ResultCache {
+    fn get<I, O>(&mut self, key: (usize, u64), input: &I) -> Option<(O, usize)>
+    where
+        I: Set + ?Sized,
+        I::Output: PartialEq + 'static,
+        O: Clone + 'static,
+    {
+        let index = self.entries.iter().position(|(k, entry)| {
+            *k == key
+                && entry
+                    .downcast_ref::<(Vec<I::Output>, (O, usize))>()
+                    .is_some_and(|(stored, _)| {
+                        Vec::len(stored) == input.len()
+                            && stored.iter().enumerate().all(|(i, e)| e == input.get(i))
+                    })
+        })?;
+        let entry = self.entries.remove(index);
+        let result = entry
+            .1
+            .downcast_ref::<(Vec<I::Output>, (O, usize))>()
+            .unwrap()
+            .1
+            .clone();
+        self.entries.push(entry);
+        Some(result)
+    }
+
+    fn insert<E, O>(&mut self, key: (usize, u64), input: Vec<E>, value: (O, usize))
+    where
+        E: Send + 'static,
+        O: Send + 'static,
+    {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.remove(0);
+        }
+        self.entries.push((key, Box::new((input, value))));
+    }
+}
+
+impl ParserContext<'_> {
+    pub fn with_result_cache(self, capacity: usize) -> Self {
+        self.result_cache.lock().unwrap().capacity = capacity;
+        self
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R>
+where
+    I::Output: Hash + PartialEq + Clone + Send + 'static,
+    R::Output: Clone + Send + 'static,
+{
+    /// Like `parse`, but reuses results for identical inputs once `with_result_cache` is set.
+    pub fn parse_cached(&self, input: &I) -> Result<R::Output> {
+        let mut hasher = DefaultHasher::new();
+        input.len().hash(&mut hasher);
+        for i in 0..input.len() {
+            input.get(i).hash(&mut hasher);
+        }
+        let key = (self.raw as *const R as *const () as usize, hasher.finish());
+        let mut cache = self.context.result_cache.lock().unwrap();
+        if let Some((result, _)) = cache.get::<I, R::Output>(key, input) {
+            return Ok(result);
+        }
+        let capacity = cache.capacity;
+        drop(cache);
+        let (result, end) = self.run_top_level(input)?;
+        if capacity > 0 {
+            let elements = (0..input.len()).map(|i| input.get(i).clone()).collect();
+            self.context
+                .result_cache
+                .lock()
+                .unwrap()
+                .insert::<I::Output, _>(key, elements, (result.clone(), end));
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::ResultCache;
+    use crate::parsers::*;
+
+    #[test]
+    fn parse_cached_skips_repeated_input() {
+        let chars = "abc".chars().collect::<Vec<_>>();
+        let calls = Cell::new(0);
+        let context = ParserContext::new().with_result_cache(2);
+        let probe = context.new_parser(|input: &Vec<char>, start| {
+            calls.set(calls.get() + 1);
+            Ok((input[start..].iter().collect::<String>(), input.len()))
+        });
+        assert_eq!(probe.parse_cached(&chars).unwrap(), "abc");
+        assert_eq!(probe.parse_cached(&chars).unwrap(), "abc");
+        assert_eq!(calls.get(), 1);
+        assert_eq!(probe.parse_cached(&"abd".chars().collect()).unwrap(), "abd");
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn result_cache_compares_input_on_hit() {
+        let mut cache = ResultCache {
+            capacity: 1,
+            ..Default::default()
+        };
+        cache.insert((0, 0), vec!['a'], ("a", 1));
+        assert_eq!(cache.get::<[char], &str>((0, 0), &['b'][..]), None);
+        assert_eq!(
+            cache.get::<[char], &str>((0, 0), &['a'][..]),
+            Some(("a", 1))
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use super::{Error, Set};
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CaretError {
+    pub line: usize,
+    pub col: usize,
+    pub len: usize,
+    pub message: String,
+    pub expected: Vec<String>,
+}
+
+fn focus(error: &Error) -> &Error {
+    match error {
+        Error::Add(l) => l
+            .iter()
+            .rev()
+            .find(|e| !matches!(e, Error::Succeed(_)))
+            .map_or(error, focus),
+        Error::Fatal(inner) => focus(inner),
+        _ => error,
+    }
+}
+
+fn collect_expected(error: &Error, expected: &mut Vec<String>) {
+    let hint = match error {
+        Error::Hinted(_, hint) => hint.as_str(),
+        Error::StaticHint(_, hint) => hint,
+        Error::Add(l) | Error::Or(l) => {
+            l.iter().for_each(|e| collect_expected(e, expected));
+            return;
+        }
+        Error::Fatal(inner) => return collect_expected(inner, expected),
+        Error::Single(..) | Error::Succeed(_) | Error::Incomplete(_) | Error::Trailing(_) => return,
+    };
+    if !expected.iter().any(|e| e == hint) {
+        expected.push(hint.to_string());
+    }
+}
+
+impl Error {
+    pub fn to_caret<I: Set<Output = char> + ?Sized>(&self, input: &I) -> CaretError {
+        let error = focus(self);
+        let (line, col) = error.line_col_tabstop(input, 1);
+        let mut expected = Vec::new();
+        collect_expected(error, &mut expected);
+        CaretError {
+            line,
+            col,
+            len: error.range().len(),
+            message: error.to_string(),
+            expected,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::*;
+
+    #[test]
+    fn to_caret_locates_multiline_errors() {
+        let chars = "let\nx = ?".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let value = context.single('1').label("number") | context.single('"').label("string");
+        let binding = !context.str_tag("let\nx = ") + value;
+        let caret = binding.parse(&chars).unwrap_err().to_caret(&chars);
+        assert_eq!((caret.line, caret.col, caret.len), (2, 5, 1));
+        assert_eq!(caret.expected, vec!["number", "string"]);
+    }
+}
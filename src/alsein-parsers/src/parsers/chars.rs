@@ -0,0 +1,361 @@
+use std::ops::Range;
+
+use super::{Error, Normalized, Parser, ParserContext, RawParser, Set};
+
+impl<'a> ParserContext<'a> {
+    pub fn chars_while<I: Set<Output = char> + ?Sized>(
+        &'a self,
+        pred: fn(char) -> bool,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Range<usize>>> {
+        self.new_parser(move |input: &I, start| {
+            let len = input.len();
+            let mut end = start;
+            while end < len && pred(*input.get(end)) {
+                end += 1;
+            }
+            Ok((start..end, end))
+        })
+    }
+
+    pub fn str_tag<I: Set<Output = char> + ?Sized>(
+        &'a self,
+        value: &str,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Vec<char>>> {
+        self.tag_with(value.chars().collect(), |chars| {
+            format!("{:?}", chars.iter().collect::<String>())
+        })
+    }
+
+    pub fn none_of_str<I: Set<Output = char> + ?Sized>(
+        &'a self,
+        excluded: &str,
+    ) -> Parser<'a, I, impl RawParser<I, Output = char>> {
+        let excluded = excluded.chars().collect::<Vec<_>>();
+        self.new_parser(move |input: &I, start| {
+            if start >= input.len() {
+                Err(Error::Incomplete(start))
+            } else if excluded.contains(input.get(start)) {
+                Err(Error::Single(0.0, start))
+            } else {
+                Ok((*input.get(start), start + 1))
+            }
+        })
+    }
+
+    pub fn keywords<I: Set<Output = char> + ?Sized>(
+        &'a self,
+        keywords: &[&str],
+    ) -> Parser<'a, I, impl RawParser<I, Output = String>> {
+        let keywords = keywords.iter().map(|k| k.to_string()).collect::<Vec<_>>();
+        self.new_parser(move |input: &I, start| {
+            let mut end = start;
+            while end < input.len() && (input.get(end).is_alphanumeric() || *input.get(end) == '_')
+            {
+                end += 1;
+            }
+            let word = (start..end).map(|i| *input.get(i)).collect::<String>();
+            if keywords.contains(&word) {
+                return Ok((word, end));
+            }
+            let nearest = keywords
+                .iter()
+                .map(|k| (levenshtein(k, &word), k))
+                .min_by_key(|&(distance, _)| distance);
+            let hint = match nearest {
+                Some((distance, nearest))
+                    if end > start && distance <= (end - start).div_ceil(3) =>
+                {
+                    format!("unknown keyword {:?}, did you mean {:?}?", word, nearest)
+                }
+                _ => format!("expected one of {:?}", keywords),
+            };
+            Err(Error::Hinted(Box::new(Error::Single(0.0, start)), hint))
+        })
+    }
+
+    pub fn with_normalizer<
+        I: Set + ?Sized,
+        O: 'static,
+        R: RawParser<Normalized<I, O>> + ?Sized + 'a,
+    >(
+        &'a self,
+        normalize: fn(&I::Output) -> O,
+        parser: Parser<'a, Normalized<I, O>, R>,
+    ) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.new_parser(move |input: &I, start| {
+            parser.run(&Normalized::new(input, normalize), start)
+        })
+    }
+
+    pub fn unicode_escape<I: Set<Output = char> + ?Sized>(
+        &'a self,
+    ) -> Parser<'a, I, impl RawParser<I, Output = char> + 'a> {
+        let prefix = self.str_tag("\\u{");
+        self.new_parser(move |input: &I, start| {
+            let (_, digits_start) = prefix.run(input, start)?;
+            let mut pos = digits_start;
+            while pos < input.len() && pos - digits_start < 6 && input.get(pos).is_ascii_hexdigit()
+            {
+                pos += 1;
+            }
+            if pos == digits_start {
+                return Err(Error::Single(0.0, pos));
+            }
+            if pos >= input.len() || *input.get(pos) != '}' {
+                return Err(Error::Single(0.0, pos));
+            }
+            let digits = (digits_start..pos)
+                .map(|i| *input.get(i))
+                .collect::<String>();
+            let code = u32::from_str_radix(&digits, 16).unwrap();
+            match std::char::from_u32(code) {
+                Some(c) => Ok((c, pos + 1)),
+                None => Err(Error::Hinted(
+                    Box::new(Error::Single(0.0, digits_start)),
+                    format!("invalid unicode scalar value U+{:X}", code),
+                )),
+            }
+        })
+    }
+
+    pub fn escaped<I: Set<Output = char> + ?Sized>(
+        &'a self,
+        quote: char,
+    ) -> Parser<'a, I, impl RawParser<I, Output = String> + 'a> {
+        let unicode = self.unicode_escape();
+        self.new_parser(move |input: &I, start| {
+            if start >= input.len() || *input.get(start) != quote {
+                return Err(Error::Single(0.0, start));
+            }
+            let mut value = String::new();
+            let mut pos = start + 1;
+            loop {
+                if pos >= input.len() {
+                    return Err(Error::Incomplete(pos));
+                }
+                let c = match *input.get(pos) {
+                    c if c == quote => return Ok((value, pos + 1)),
+                    '\\' if pos + 1 < input.len() && *input.get(pos + 1) == 'u' => {
+                        let (c, end) = unicode
+                            .run(input, pos)
+                            .map_err(|e| Error::Succeed(start..pos) + e)?;
+                        value.push(c);
+                        pos = end;
+                        continue;
+                    }
+                    '\\' if pos + 1 >= input.len() => return Err(Error::Incomplete(pos + 1)),
+                    '\\' => {
+                        pos += 1;
+                        match *input.get(pos) {
+                            'n' => '\n',
+                            'r' => '\r',
+                            't' => '\t',
+                            '0' => '\0',
+                            c if c == '\\' || c == quote => c,
+                            c => {
+                                return Err(Error::Hinted(
+                                    Box::new(Error::Single(0.0, pos)),
+                                    format!("unknown escape sequence \\{}", c),
+                                ))
+                            }
+                        }
+                    }
+                    c => c,
+                };
+                value.push(c);
+                pos += 1;
+            }
+        })
+    }
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitute = diagonal + (ca != cb) as usize;
+            diagonal = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+impl<'a, I: Set<Output = char> + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R> {
+    pub fn text(self) -> Parser<'a, I, impl RawParser<I, Output = String> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            let (_, end) = self.run(input, start)?;
+            Ok(((start..end).map(|i| *input.get(i)).collect(), end))
+        })
+    }
+}
+
+impl Error {
+    pub fn line_col_tabstop<I: Set<Output = char> + ?Sized>(
+        &self,
+        input: &I,
+        tab_width: usize,
+    ) -> (usize, usize) {
+        let offset = self.range().start.min(input.len());
+        let (mut line, mut col) = (1, 1);
+        for i in 0..offset {
+            match *input.get(i) {
+                '\n' => {
+                    line += 1;
+                    col = 1;
+                }
+                '\t' if tab_width > 0 => col += tab_width - (col - 1) % tab_width,
+                _ => col += 1,
+            }
+        }
+        (line, col)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::parsers::*;
+
+    #[test]
+    fn chars_while_matches_run() {
+        let chars = "abc123".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let word = context.chars_while(char::is_alphabetic);
+        assert_eq!(word.parse(&chars).unwrap(), 0..3);
+        let digits = context.chars_while(|c| c.is_ascii_digit());
+        assert_eq!(digits.parse(&chars).unwrap(), 0..0);
+    }
+
+    #[test]
+    fn none_of_str_excludes_chars() {
+        let context = ParserContext::new();
+        let body = context.none_of_str("\"\\");
+        assert_eq!(body.parse(&vec!['a']).unwrap(), 'a');
+        assert!(matches!(body.parse(&vec!['"']), Err(Error::Single(_, 0))));
+        assert!(matches!(body.parse(&vec![]), Err(Error::Incomplete(0))));
+    }
+
+    #[test]
+    fn str_tag_explains_mismatch() {
+        let chars = "functoin".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let error = context.str_tag("function").parse(&chars).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "expected \"function\", found \"functoin\" (at offset 5)"
+        );
+        assert_eq!(error.range(), 5..6);
+    }
+
+    #[test]
+    fn line_col_tabstop_expands_tabs() {
+        let chars = "let\n\tx = ?".chars().collect::<Vec<_>>();
+        let error = Error::Single(0.0, 5);
+        assert_eq!(error.line_col_tabstop(&chars, 4), (2, 5));
+        let error = Error::Single(0.0, 4);
+        assert_eq!(error.line_col_tabstop(&chars, 4), (2, 1));
+    }
+
+    #[test]
+    fn unicode_escape_validates_scalar_values() {
+        let context = ParserContext::new();
+        let escape = context.unicode_escape();
+        let chars = "\\u{41}".chars().collect::<Vec<_>>();
+        assert_eq!(escape.parse(&chars).unwrap(), 'A');
+        let chars = "\\u{110000}".chars().collect::<Vec<_>>();
+        let error = escape.parse(&chars).unwrap_err();
+        assert_eq!(error.to_string(), "invalid unicode scalar value U+110000");
+        let chars = "\\u{D800}".chars().collect::<Vec<_>>();
+        assert!(escape.parse(&chars).is_err());
+    }
+
+    #[test]
+    fn escaped_decodes_unicode_escapes() {
+        let context = ParserContext::new();
+        let string = context.escaped('"');
+        let chars = r#""a\u{41}\n\"""#.chars().collect::<Vec<_>>();
+        assert_eq!(string.parse(&chars).unwrap(), "aA\n\"");
+        let chars = r#""\u{110000}""#.chars().collect::<Vec<_>>();
+        let error = string.parse(&chars).unwrap_err();
+        assert!(matches!(
+            &error,
+            Error::Add(l) if l[1].to_string() == "invalid unicode scalar value U+110000"
+        ));
+        assert!(matches!(
+            string.parse(&r#""\q""#.chars().collect()),
+            Err(Error::Hinted(..))
+        ));
+        assert!(matches!(
+            string.parse(&r#""ab"#.chars().collect()),
+            Err(Error::Incomplete(3))
+        ));
+    }
+
+    #[test]
+    fn text_returns_matched_characters() {
+        let chars = "42x".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let int = context.chars_while(|c| c.is_ascii_digit()).map(|r| {
+            r.map(|i| chars[i].to_digit(10).unwrap())
+                .fold(0, |n, d| n * 10 + d)
+        });
+        assert_eq!(int.parse_partial(&chars).unwrap().0, 42);
+        assert_eq!(int.text().parse_partial(&chars).unwrap().0, "42");
+    }
+
+    #[test]
+    fn keywords_suggest_nearest_candidate() {
+        let context = ParserContext::new();
+        let keyword = context.keywords(&["if", "else", "while"]);
+        let chars = "else".chars().collect::<Vec<_>>();
+        assert_eq!(keyword.parse(&chars).unwrap(), "else");
+        let chars = "esle".chars().collect::<Vec<_>>();
+        assert_eq!(
+            keyword.parse(&chars).unwrap_err().to_string(),
+            "unknown keyword \"esle\", did you mean \"else\"?"
+        );
+        let chars = "loop".chars().collect::<Vec<_>>();
+        assert_eq!(
+            keyword.parse(&chars).unwrap_err().to_string(),
+            "expected one of [\"if\", \"else\", \"while\"]"
+        );
+    }
+
+    #[test]
+    fn with_normalizer_matches_case_insensitively() {
+        let chars = "x=TRUE".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let value =
+            context.with_normalizer(|c: &char| c.to_ascii_lowercase(), context.str_tag("true"));
+        let setting = !context.str_tag("x=") + value.map_end(|_, end| end);
+        assert_eq!(setting.parse(&chars).unwrap(), 6);
+        assert!(context
+            .str_tag("true")
+            .parse(&"TRUE".chars().collect::<Vec<_>>())
+            .is_err());
+    }
+
+    #[test]
+    fn with_normalizer_maps_lazily() {
+        static MAPPED: AtomicUsize = AtomicUsize::new(0);
+        let bytes = b"ok, and then a long tail".to_vec();
+        let context = ParserContext::new();
+        let ok = context.with_normalizer(
+            |&b: &u8| {
+                MAPPED.fetch_add(1, Ordering::Relaxed);
+                b as char
+            },
+            context.str_tag("ok"),
+        );
+        assert_eq!(ok.parse(&bytes).unwrap(), vec!['o', 'k']);
+        assert_eq!(MAPPED.load(Ordering::Relaxed), 2);
+        let chars = vec!['A'; 100_000];
+        let a = context.with_normalizer(|c: &char| c.to_ascii_lowercase(), context.single('a'));
+        assert_eq!(a.many0().parse(&chars).unwrap().len(), 100_000);
+    }
+}
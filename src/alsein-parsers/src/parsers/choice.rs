@@ -0,0 +1,400 @@
+use std::cmp::Ordering;
+
+use super::{Error, ErrorKind, Parser, ParserContext, ParserDyn, ParserResult, RawParser, Set};
+
+pub type Priority = i32;
+
+pub trait Alternatives<'a, I: Set, O> {
+    fn into_parsers(self) -> Vec<ParserDyn<'a, I, O>>;
+}
+
+macro_rules! impl_alternatives {
+    ($($r:ident $f:ident $i:tt),+) => {
+        impl<'a, I: Set, O: 'a, $($r: RawParser<I> + 'a, $f: Fn($r::Output) -> O + 'a),+>
+            Alternatives<'a, I, O> for ($((Parser<'a, I, $r>, $f),)+)
+        {
+            fn into_parsers(self) -> Vec<ParserDyn<'a, I, O>> {
+                vec![$({
+                    let (parser, f) = self.$i;
+                    parser.map(f).into_dyn()
+                }),+]
+            }
+        }
+    };
+}
+
+impl_alternatives!(R0 F0 0);
+impl_alternatives!(R0 F0 0, R1 F1 1);
+impl_alternatives!(R0 F0 0, R1 F1 1, R2 F2 2);
+impl_alternatives!(R0 F0 0, R1 F1 1, R2 F2 2, R3 F3 3);
+impl_alternatives!(R0 F0 0, R1 F1 1, R2 F2 2, R3 F3 3, R4 F4 4);
+impl_alternatives!(R0 F0 0, R1 F1 1, R2 F2 2, R3 F3 3, R4 F4 4, R5 F5 5);
+impl_alternatives!(R0 F0 0, R1 F1 1, R2 F2 2, R3 F3 3, R4 F4 4, R5 F5 5, R6 F6 6);
+impl_alternatives!(R0 F0 0, R1 F1 1, R2 F2 2, R3 F3 3, R4 F4 4, R5 F5 5, R6 F6 6, R7 F7 7);
+
+fn parse_first<'a, I: Set + ?Sized, O>(
+    context: &ParserContext<'a>,
+    parsers: &[ParserDyn<'a, I, O>],
+    input: &I,
+    start: usize,
+) -> ParserResult<O> {
+    let mut error: Option<Error> = None;
+    for parser in parsers {
+        let snapshot = context.snapshot();
+        match parser.run(input, start) {
+            Ok(r) => return Ok(r),
+            Err(e) if e.kind() == ErrorKind::Hard => return Err(e),
+            Err(e) => {
+                context.restore(snapshot);
+                error = Some(match error.take() {
+                    Some(prev) => prev | e,
+                    None => e,
+                })
+            }
+        }
+    }
+    Err(error.unwrap_or(Error::Single(0.0, start)))
+}
+
+impl<'a> ParserContext<'a> {
+    pub fn alt_map<I: Set + 'a, O: 'a>(
+        &'a self,
+        alternatives: impl Alternatives<'a, I, O>,
+    ) -> Parser<'a, I, impl RawParser<I, Output = O> + 'a> {
+        let parsers = alternatives.into_parsers();
+        self.new_parser(move |input: &I, start| parse_first(self, &parsers, input, start))
+    }
+
+    pub fn choice<I: Set + ?Sized + 'a, O: 'a>(
+        &'a self,
+        parsers: Vec<ParserDyn<'a, I, O>>,
+    ) -> Parser<'a, I, impl RawParser<I, Output = O> + 'a> {
+        self.new_parser(move |input: &I, start| parse_first(self, &parsers, input, start))
+    }
+
+    pub fn one_or_many<
+        I: Set + ?Sized,
+        T: 'a,
+        R1: RawParser<I, Output = T> + ?Sized + 'a,
+        R2: RawParser<I, Output = Vec<T>> + ?Sized + 'a,
+    >(
+        &'a self,
+        single: Parser<'a, I, R1>,
+        list: Parser<'a, I, R2>,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Vec<T>> + 'a> {
+        list | single.map(|v| vec![v])
+    }
+
+    pub fn checked_choice<I: Set + ?Sized + 'a, O: 'a>(
+        &'a self,
+        parsers: Vec<ParserDyn<'a, I, O>>,
+    ) -> Parser<'a, I, impl RawParser<I, Output = O> + 'a> {
+        self.new_parser(move |input: &I, start| {
+            let mut result: Option<(usize, (O, usize))> = None;
+            let mut error: Option<Error> = None;
+            for (i, parser) in parsers.iter().enumerate() {
+                let snapshot = self.snapshot();
+                match (parser.run(input, start), &result) {
+                    (Ok(r), None) => result = Some((i, r)),
+                    (Ok(_), &Some((first, _))) => {
+                        self.restore(snapshot);
+                        self.warn(format!(
+                            "alternative {} is shadowed by alternative {} at {}",
+                            i, first, start
+                        ))
+                    }
+                    (Err(e), None) if e.kind() == ErrorKind::Hard => return Err(e),
+                    (Err(e), _) => {
+                        self.restore(snapshot);
+                        error = Some(match error.take() {
+                            Some(prev) => prev | e,
+                            None => e,
+                        })
+                    }
+                }
+            }
+            match result {
+                Some((_, r)) => Ok(r),
+                None => Err(error.unwrap_or(Error::Single(0.0, start))),
+            }
+        })
+    }
+
+    pub fn fallback_chain<I: Set + ?Sized + 'a, O: 'a>(
+        &'a self,
+        parsers: Vec<ParserDyn<'a, I, O>>,
+    ) -> Parser<'a, I, impl RawParser<I, Output = O> + 'a> {
+        self.new_parser(move |input: &I, start| {
+            let mut error = Error::Single(0.0, start);
+            for parser in parsers.iter() {
+                let snapshot = self.snapshot();
+                match parser.run(input, start) {
+                    Ok(r) => return Ok(r),
+                    Err(e) if e.kind() == ErrorKind::Hard => return Err(e),
+                    Err(e) => {
+                        self.restore(snapshot);
+                        error = e;
+                    }
+                }
+            }
+            Err(error)
+        })
+    }
+
+    pub fn longest_match<I: Set + 'a, O: 'a>(
+        &'a self,
+        parsers: Vec<(Priority, ParserDyn<'a, I, O>)>,
+    ) -> ParserDyn<'a, I, O> {
+        self.new_parser(move |input: &I, start| {
+            let snapshot = self.snapshot();
+            let mut best: Option<(Priority, usize, ParserDyn<'a, I, O>)> = None;
+            let mut error: Option<Error> = None;
+            for &(priority, parser) in parsers.iter() {
+                let result = parser.run(input, start);
+                self.restore(snapshot);
+                match result {
+                    Ok((_, end)) => match &best {
+                        Some((p, e, _)) if (*e, *p) >= (end, priority) => {}
+                        _ => best = Some((priority, end, parser)),
+                    },
+                    Err(e) if e.kind() == ErrorKind::Hard => return Err(e),
+                    Err(e) => {
+                        error = Some(match error.take() {
+                            Some(prev) => prev | e,
+                            None => e,
+                        })
+                    }
+                }
+            }
+            match best {
+                Some((_, _, parser)) => parser.run(input, start),
+                None => Err(error.unwrap_or(Error::Single(0.0, start))),
+            }
+        })
+        .into_dyn()
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R> {
+    fn choose<R2: RawParser<I, Output = R::Output> + ?Sized + 'a>(
+        self,
+        other: Parser<'a, I, R2>,
+        chooser: impl Fn(&(R::Output, usize), &(R::Output, usize)) -> Ordering + 'a,
+    ) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            let snapshot = self.context.snapshot();
+            let left = match self.run(input, start) {
+                Err(e) if e.kind() == ErrorKind::Hard => return Err(e),
+                Err(e) => {
+                    self.context.restore(snapshot);
+                    Err(e)
+                }
+                Ok(l) => Ok(l),
+            };
+            let chosen = self.context.snapshot();
+            match (left, other.run(input, start)) {
+                (_, Err(e)) if e.kind() == ErrorKind::Hard => Err(e),
+                (Ok(l), Ok(r)) if chooser(&l, &r) == Ordering::Less => {
+                    self.context.restore(snapshot);
+                    other.run(input, start)
+                }
+                (Ok(l), _) => {
+                    self.context.restore(chosen);
+                    Ok(l)
+                }
+                (Err(_), Ok(r)) => Ok(r),
+                (Err(e1), Err(e2)) => {
+                    self.context.restore(snapshot);
+                    Err(e1 | e2)
+                }
+            }
+        })
+    }
+
+    pub fn prefer<R2: RawParser<I, Output = R::Output> + ?Sized + 'a>(
+        self,
+        other: Parser<'a, I, R2>,
+        chooser: impl Fn(&R::Output, &R::Output) -> Ordering + 'a,
+    ) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.choose(other, move |l, r| chooser(&l.0, &r.0))
+    }
+
+    pub fn longest<R2: RawParser<I, Output = R::Output> + ?Sized + 'a>(
+        self,
+        other: Parser<'a, I, R2>,
+    ) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.choose(other, |l, r| l.1.cmp(&r.1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::*;
+
+    #[test]
+    fn checked_choice_warns_on_shadowing() {
+        let chars = "let".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let identifier = context.chars_while(char::is_alphabetic).map(|r| r.len());
+        let keyword = context.tag("let".chars().collect()).map(|v| v.len());
+        let choice = context.checked_choice(vec![identifier.into_dyn(), keyword.into_dyn()]);
+        assert_eq!(choice.parse(&chars).unwrap(), 3);
+        assert_eq!(
+            context.take_warnings(),
+            vec!["alternative 1 is shadowed by alternative 0 at 0"]
+        );
+        let keyword = keyword.emit(|_| Some("keyword"));
+        let choice = context.checked_choice(vec![identifier.into_dyn(), keyword.into_dyn()]);
+        assert_eq!(choice.parse(&chars).unwrap(), 3);
+        assert!(context.emitted::<&str>().is_empty());
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Node {
+        Keyword(Vec<char>),
+        Name(char),
+        Number(usize),
+    }
+
+    #[test]
+    fn alt_map_builds_common_node() {
+        let context = ParserContext::new();
+        let node = context.alt_map((
+            (context.tag("let".chars().collect()), Node::Keyword),
+            (context.single('x'), Node::Name),
+            (
+                context.chars_while(|c| c.is_ascii_digit()),
+                |r: std::ops::Range<usize>| Node::Number(r.len()),
+            ),
+        ));
+        let parse = |s: &str| node.parse(&s.chars().collect::<Vec<_>>()).unwrap();
+        assert_eq!(parse("let"), Node::Keyword(vec!['l', 'e', 't']));
+        assert_eq!(parse("x"), Node::Name('x'));
+        assert_eq!(parse("42"), Node::Number(2));
+    }
+
+    #[test]
+    fn one_or_many_accepts_both_shapes() {
+        let context = ParserContext::new();
+        let digit = context.single('1') | context.single('2') | context.single('3');
+        let items = context.separated_min_seps(digit, !context.single(','), 0);
+        let list = !context.single('[') + items + !context.single(']');
+        let value = context.one_or_many(digit, list);
+        let parse = |s: &str| value.parse(&s.chars().collect::<Vec<_>>()).unwrap();
+        assert_eq!(parse("2"), vec!['2']);
+        assert_eq!(parse("[1,2,3]"), vec!['1', '2', '3']);
+    }
+
+    #[test]
+    fn longest_match_breaks_ties_by_priority() {
+        #[derive(Debug, PartialEq)]
+        enum Token {
+            Keyword,
+            Identifier,
+        }
+        let chars = "if".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let identifier = context
+            .chars_while(char::is_alphabetic)
+            .map(|_| Token::Identifier);
+        let keyword = context.str_tag("if").map(|_| Token::Keyword);
+        let lexer =
+            context.longest_match(vec![(0, identifier.into_dyn()), (1, keyword.into_dyn())]);
+        assert_eq!(lexer.parse(&chars).unwrap(), Token::Keyword);
+        let chars = "iffy".chars().collect::<Vec<_>>();
+        assert_eq!(lexer.parse(&chars).unwrap(), Token::Identifier);
+    }
+
+    #[test]
+    fn fallback_chain_reports_most_lenient_error() {
+        let context = ParserContext::new();
+        let strict = context.str_tag("true").map(|_| true).label("strict");
+        let lenient = context
+            .one_of_tokens(&['t', 'y', '1'])
+            .map(|_| true)
+            .label("lenient");
+        let boolean = context.fallback_chain(vec![strict.into_dyn(), lenient.into_dyn()]);
+        let chars = "yes".chars().collect::<Vec<_>>();
+        assert!(strict.parse(&chars).is_err());
+        assert!(boolean.parse(&chars).unwrap());
+        let chars = "no".chars().collect::<Vec<_>>();
+        assert_eq!(boolean.parse(&chars).unwrap_err().to_string(), "lenient");
+    }
+
+    #[test]
+    fn prefer_picks_by_comparator() {
+        let chars = "ab".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let bare = context.single('a').map(|c| vec![c]);
+        let rich = (context.single('a') + context.single('b')).map(|(a, b)| vec![a, b]);
+        let choice = bare.prefer(rich, |l, r| l.len().cmp(&r.len()));
+        assert_eq!(choice.parse(&chars).unwrap(), vec!['a', 'b']);
+        assert_eq!((bare | rich).parse(&chars).unwrap(), vec!['a']);
+        let chars = "c".chars().collect::<Vec<_>>();
+        assert!(matches!(choice.parse(&chars), Err(Error::Or(_))));
+    }
+
+    #[test]
+    fn choice_matches_bitor_errors() {
+        let context = ParserContext::new();
+        let [a, b, c] = ['a', 'b', 'c'].map(|t| context.single(t));
+        let choice = context.choice(vec![a.into_dyn(), b.into_dyn(), c.into_dyn()]);
+        let chars = "c".chars().collect::<Vec<_>>();
+        assert_eq!(choice.parse(&chars).unwrap(), 'c');
+        let chars = "d".chars().collect::<Vec<_>>();
+        assert_eq!(
+            format!("{:?}", choice.parse(&chars).unwrap_err()),
+            format!("{:?}", (a | b | c).parse(&chars).unwrap_err())
+        );
+    }
+
+    #[test]
+    fn choose_discards_losing_branch() {
+        let chars = "ab".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let short = context.single('a').emit(|_| Some("short"));
+        let long = (context.single('a') + context.single('b')).map(|(a, _)| a);
+        let choice = short.longest(long.emit(|_| Some("long")));
+        assert_eq!(choice.parse(&chars).unwrap(), 'a');
+        assert_eq!(context.emitted::<&str>(), vec!["long"]);
+        let chars = "a".chars().collect::<Vec<_>>();
+        let cut = context.single('b').cut().longest(context.single('a'));
+        assert_eq!(cut.parse(&chars).unwrap_err().kind(), ErrorKind::Hard);
+    }
+
+    #[test]
+    fn longest_match_discards_losers() {
+        let context = ParserContext::new();
+        let short = context.single('a').emit(|_| Some("short"));
+        let moded = context.single('a') + context.push_mode("m");
+        let long = (context.single('a') + context.single('b')).map(|(a, _)| a);
+        let lexer = context.longest_match(vec![
+            (0, short.into_dyn()),
+            (1, moded.into_dyn()),
+            (0, long.emit(|_| Some("long")).into_dyn()),
+        ]);
+        let chars = "ab".chars().collect::<Vec<_>>();
+        assert_eq!(lexer.parse(&chars).unwrap(), 'a');
+        assert_eq!(context.emitted::<&str>(), vec!["long"]);
+        assert_eq!(context.current_mode(), None);
+        let chars = "a".chars().collect::<Vec<_>>();
+        assert_eq!(lexer.parse(&chars).unwrap(), 'a');
+        assert!(context.emitted::<&str>().is_empty());
+        assert_eq!(context.current_mode(), Some("m"));
+    }
+
+    #[test]
+    fn longest_picks_furthest_alternative() {
+        let context = ParserContext::new();
+        let equals = context.str_tag("=").map(|_| "=");
+        let arrow = context.str_tag("=>").map(|_| "=>");
+        let operator = equals.longest(arrow);
+        let chars = "=>".chars().collect::<Vec<_>>();
+        assert_eq!(operator.parse(&chars).unwrap(), "=>");
+        assert_eq!((equals | arrow).parse(&chars).unwrap(), "=");
+        let chars = "=".chars().collect::<Vec<_>>();
+        assert_eq!(operator.parse(&chars).unwrap(), "=");
+        let same = equals.longest(context.str_tag("=").map(|_| "eq"));
+        assert_eq!(same.parse(&chars).unwrap(), "=");
+    }
+}
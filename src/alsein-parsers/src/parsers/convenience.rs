@@ -0,0 +1,45 @@
+use super::{Error, ParserContext, Result};
+
+fn whole<O>(chars: &[char], (result, rest): (O, &[char])) -> Result<O> {
+    if rest.is_empty() {
+        Ok(result)
+    } else {
+        Err(Error::Single(0.0, chars.len() - rest.len()))
+    }
+}
+
+pub fn digits(input: &str) -> Result<String> {
+    let chars = input.chars().collect::<Vec<_>>();
+    let context = ParserContext::new();
+    let digits = context
+        .chars_while(|c| c.is_ascii_digit())
+        .require_progress()
+        .text();
+    whole(&chars, digits.parse_partial(&chars)?)
+}
+
+pub fn integer(input: &str) -> Result<i64> {
+    let chars = input.chars().collect::<Vec<_>>();
+    let context = ParserContext::new();
+    let sign = context.one_of_tokens(&['+', '-']).map(Some) | context.marker().map(|_| None);
+    let digits = context
+        .chars_while(|c| c.is_ascii_digit())
+        .require_progress();
+    let text = whole(&chars, (sign + digits).text().parse_partial(&chars)?)?;
+    text.parse()
+        .map_err(|e| Error::Hinted(Box::new(Error::Single(0.0, 0)), format!("{}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::*;
+
+    #[test]
+    fn free_functions_parse_whole_strings() {
+        assert_eq!(integer("123").unwrap(), 123);
+        assert_eq!(integer("-7").unwrap(), -7);
+        assert!(integer("12a").is_err());
+        assert_eq!(digits("0042").unwrap(), "0042");
+        assert!(digits("abc").is_err());
+    }
+}
@@ -0,0 +1,133 @@
+use std::{
+    collections::HashSet,
+    panic::{self, AssertUnwindSafe},
+    sync::atomic::Ordering,
+    time::{Duration, Instant},
+};
+
+use super::{Error, Parser, ParserContext, RawParser, Result, Set};
+
+#[derive(Debug, Default)]
+pub struct ProfileStats {
+    pub invocations: usize,
+    pub successes: usize,
+    pub failures: usize,
+    pub positions: HashSet<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ParseMetrics {
+    pub elapsed: Duration,
+    pub steps: usize,
+}
+
+impl<'a> ParserContext<'a> {
+    pub fn profile_report(&self) -> String {
+        let profiles = self.profiles.lock().unwrap();
+        let mut rows = profiles.iter().collect::<Vec<_>>();
+        rows.sort_by(|(n1, s1), (n2, s2)| s2.invocations.cmp(&s1.invocations).then(n1.cmp(n2)));
+        let mut report = format!(
+            "{:<16}{:>8}{:>8}{:>8}{:>10}\n",
+            "parser", "calls", "ok", "fail", "positions"
+        );
+        for (name, stats) in rows {
+            report += &format!(
+                "{:<16}{:>8}{:>8}{:>8}{:>10}\n",
+                name,
+                stats.invocations,
+                stats.successes,
+                stats.failures,
+                stats.positions.len()
+            );
+        }
+        report
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R> {
+    pub fn timed(&self, input: &I) -> (Result<R::Output>, ParseMetrics) {
+        let now = Instant::now();
+        let result = self.run_top_level(input).map(|(result, _)| result);
+        let metrics = ParseMetrics {
+            elapsed: now.elapsed(),
+            steps: self.context.steps.load(Ordering::Relaxed),
+        };
+        (result, metrics)
+    }
+
+    pub fn profile(self, name: &str) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        let name = name.to_string();
+        self.context.new_parser(move |input: &I, start| {
+            let result = self.run(input, start);
+            let mut profiles = self.context.profiles.lock().unwrap();
+            let stats = profiles.entry(name.clone()).or_default();
+            stats.invocations += 1;
+            if result.is_ok() {
+                stats.successes += 1;
+            } else {
+                stats.failures += 1;
+            }
+            stats.positions.insert(start);
+            result
+        })
+    }
+
+    pub fn guard(self, name: &str) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        let name = name.to_string();
+        self.context.new_parser(move |input: &I, start| {
+            panic::catch_unwind(AssertUnwindSafe(|| self.run(input, start))).unwrap_or_else(|_| {
+                Err(Error::Hinted(
+                    Box::new(Error::Single(0.0, start)),
+                    format!("{} panicked at {}", name, start),
+                ))
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::*;
+
+    #[test]
+    fn guard_reports_panics() {
+        let chars = "ab".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let unchecked =
+            context.new_parser(|input: &Vec<char>, start| Ok((input[start + 5], start + 1)));
+        let guarded = context.single('a') + unchecked.guard("unchecked");
+        let error = guarded.parse(&chars).unwrap_err();
+        assert!(error.to_string().contains("unchecked panicked at 1"));
+    }
+
+    #[test]
+    fn profile_report_lists_hottest_first() {
+        let chars = "abd".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let prefix = context.tag("ab".chars().collect()).profile("prefix");
+        let d = context.single('d').profile("d");
+        let grammar = (prefix + context.single('c')) | (prefix + d);
+        grammar.parse(&chars).unwrap();
+        let report = context.profile_report();
+        let rows = report.lines().collect::<Vec<_>>();
+        assert!(rows[1].starts_with("prefix"));
+        assert_eq!(
+            rows[1].split_whitespace().collect::<Vec<_>>(),
+            ["prefix", "2", "2", "0", "1"]
+        );
+        assert_eq!(
+            rows[2].split_whitespace().collect::<Vec<_>>(),
+            ["d", "1", "1", "0", "1"]
+        );
+    }
+
+    #[test]
+    fn timed_counts_parse_steps() {
+        let chars = "aab".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let items = context.single('a').many_max(usize::MAX) + context.single('b');
+        let (result, metrics) = items.timed(&chars);
+        assert_eq!(result.unwrap(), (vec!['a', 'a'], 'b'));
+        assert_eq!(metrics.steps, 6);
+    }
+}
@@ -0,0 +1,66 @@
+use std::mem;
+
+use super::{Parser, ParserContext, RawParser, Set};
+
+impl<'a> ParserContext<'a> {
+    fn push_deferred(&self, pos: usize, action: Box<dyn FnOnce() + Send + 'a>) {
+        self.diagnostics
+            .lock()
+            .unwrap()
+            .deferred
+            .push((pos, action));
+    }
+
+    pub fn run_deferred(&self) {
+        let mut deferred = mem::take(&mut self.diagnostics.lock().unwrap().deferred);
+        deferred.sort_by_key(|&(pos, _)| pos);
+        for (_, action) in deferred {
+            action();
+        }
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R>
+where
+    R::Output: Clone + Send + 'a,
+{
+    pub fn defer(
+        self,
+        action: impl Fn(&R::Output) + Send + Sync + 'a,
+    ) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        let action = &*self.context.pool.add(Box::new(action));
+        self.context.new_parser(move |input: &I, start| {
+            let (result, end) = self.run(input, start)?;
+            let value = result.clone();
+            self.context
+                .push_deferred(start, Box::new(move || action(&value)));
+            Ok((result, end))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use crate::parsers::*;
+
+    #[test]
+    fn defer_observes_later_definitions() {
+        let chars = "ba".chars().collect::<Vec<_>>();
+        let definitions = Mutex::new(Vec::new());
+        let resolved = Mutex::new(None);
+        let context = ParserContext::new();
+        let reference = context
+            .single('b')
+            .defer(|&c| *resolved.lock().unwrap() = Some((c, definitions.lock().unwrap().clone())));
+        let definition = context.single('a').map(|c| {
+            definitions.lock().unwrap().push(c);
+            c
+        });
+        (reference + definition).parse(&chars).unwrap();
+        assert_eq!(*resolved.lock().unwrap(), None);
+        context.run_deferred();
+        assert_eq!(*resolved.lock().unwrap(), Some(('b', vec!['a'])));
+    }
+}
@@ -0,0 +1,54 @@
+use std::any::{Any, TypeId};
+
+use super::{Parser, ParserContext, RawParser, Set};
+
+impl ParserContext<'_> {
+    pub fn emitted<S: Send + 'static>(&self) -> Vec<S> {
+        let emitted = &mut self.diagnostics.lock().unwrap().emitted;
+        let (matching, rest) = std::mem::take(emitted)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(id, _)| *id == TypeId::of::<S>());
+        *emitted = rest;
+        matching
+            .into_iter()
+            .map(|(_, value)| *value.downcast::<S>().unwrap())
+            .collect()
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R> {
+    pub fn emit<S: Send + 'static>(
+        self,
+        f: impl Fn(&R::Output) -> Option<S> + 'a,
+    ) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            let (result, end) = self.run(input, start)?;
+            if let Some(symbol) = f(&result) {
+                self.context
+                    .diagnostics
+                    .lock()
+                    .unwrap()
+                    .emitted
+                    .push((TypeId::of::<S>(), Box::new(symbol) as Box<dyn Any + Send>));
+            }
+            Ok((result, end))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::*;
+
+    #[test]
+    fn emit_collects_declarations() {
+        let chars = "let a;let b;c;".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let name = context.one_of_tokens(&['a', 'b', 'c']);
+        let declaration = (!context.str_tag("let ") + name).emit(|&n| Some(n.to_string()));
+        let statement = (declaration | name) + !context.single(';');
+        statement.many_max(usize::MAX).parse(&chars).unwrap();
+        assert_eq!(context.emitted::<String>(), vec!["a", "b"]);
+        assert!(context.emitted::<String>().is_empty());
+    }
+}
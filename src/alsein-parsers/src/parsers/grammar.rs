@@ -0,0 +1,227 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    marker::PhantomData,
+};
+
+use super::{Error, ErrorKind, Parser, ParserContext, ParserDyn, ParserResult, RawParser, Set};
+
+type Seeds<O> = RefCell<HashMap<(usize, usize, usize), ParserResult<O>>>;
+
+pub struct Grammar<'a, I: Set> {
+    context: &'a ParserContext<'a>,
+    _phantom: PhantomData<fn(&I)>,
+}
+
+pub struct Rule<'a, I: Set, O> {
+    slot: &'a Cell<Option<ParserDyn<'a, I, O>>>,
+    parser: ParserDyn<'a, I, O>,
+}
+
+impl<I: Set, O> Clone for Rule<'_, I, O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<I: Set, O> Copy for Rule<'_, I, O> {}
+
+impl<'a, I: Set, O> Rule<'a, I, O> {
+    pub fn parser(self) -> ParserDyn<'a, I, O> {
+        self.parser
+    }
+}
+
+pub struct Declaration<'a, I: Set, O> {
+    slot: &'a Cell<Option<ParserDyn<'a, I, O>>>,
+}
+
+impl<'a, I: Set, O> Declaration<'a, I, O> {
+    pub fn define<R: RawParser<I, Output = O> + 'a>(self, parser: Parser<'a, I, R>) {
+        self.slot.set(Some(parser.into_dyn()));
+    }
+}
+
+impl<'a> ParserContext<'a> {
+    pub fn declare<I: Set, O: 'a>(&'a self) -> (ParserDyn<'a, I, O>, Declaration<'a, I, O>) {
+        let slot: &'a Cell<Option<ParserDyn<'a, I, O>>> = self.pool.add(Box::new(Cell::new(None)));
+        let parser = self
+            .new_parser(move |input: &I, start| match slot.get() {
+                Some(parser) => parser.run(input, start),
+                None => Err(Error::Hinted(
+                    Box::new(Error::Single(0.0, start)),
+                    "undefined grammar rule".to_string(),
+                )),
+            })
+            .into_dyn();
+        (parser, Declaration { slot })
+    }
+
+    pub fn recursive<I: Set, O: 'a, R: RawParser<I, Output = O> + 'a>(
+        &'a self,
+        f: impl FnOnce(ParserDyn<'a, I, O>) -> Parser<'a, I, R>,
+    ) -> ParserDyn<'a, I, O> {
+        let (parser, declaration) = self.declare();
+        declaration.define(f(parser));
+        parser
+    }
+
+    pub fn grammar<I: Set>(&'a self) -> Grammar<'a, I> {
+        Grammar {
+            context: self,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, I: Set> Grammar<'a, I> {
+    pub fn rule<O: 'a>(&mut self) -> Rule<'a, I, O> {
+        let (parser, Declaration { slot }) = self.context.declare();
+        Rule { slot, parser }
+    }
+
+    pub fn left_recursive_rule<O: Clone + 'a>(&mut self) -> Rule<'a, I, O> {
+        let rule = self.rule::<O>();
+        let seeds: &'a Seeds<O> = self
+            .context
+            .pool
+            .add(Box::new(RefCell::new(HashMap::new())));
+        let inner = rule.parser;
+        let parser = self
+            .context
+            .new_parser(move |input: &I, start| {
+                let key = (input as *const I as usize, input.len(), start);
+                if let Some(seed) = seeds.borrow().get(&key) {
+                    return seed.clone();
+                }
+                seeds
+                    .borrow_mut()
+                    .insert(key, Err(Error::Single(0.0, start)));
+                loop {
+                    let grown = inner.run(input, start);
+                    let mut seeds = seeds.borrow_mut();
+                    let progressed = match (&grown, &seeds[&key]) {
+                        (Err(e), _) if e.kind() == ErrorKind::Hard => {
+                            seeds.remove(&key);
+                            return grown;
+                        }
+                        (Err(_), Err(_)) => {
+                            seeds.remove(&key);
+                            return grown;
+                        }
+                        (Ok((_, end)), Ok((_, seed_end))) => end > seed_end,
+                        (Ok(_), Err(_)) => true,
+                        (Err(_), Ok(_)) => false,
+                    };
+                    if !progressed {
+                        return seeds.remove(&key).unwrap();
+                    }
+                    seeds.insert(key, grown);
+                }
+            })
+            .into_dyn();
+        Rule {
+            slot: rule.slot,
+            parser,
+        }
+    }
+
+    pub fn define<O, R: RawParser<I, Output = O> + 'a>(
+        &mut self,
+        rule: Rule<'a, I, O>,
+        parser: Parser<'a, I, R>,
+    ) {
+        Declaration { slot: rule.slot }.define(parser);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::*;
+
+    #[test]
+    fn grammar_wires_mutually_recursive_rules() {
+        let context = ParserContext::new();
+        let mut g = context.grammar::<Vec<char>>();
+        let expr = g.rule::<u32>();
+        let stmt = g.rule::<u32>();
+        let digit = context
+            .one_of_tokens(&['1', '2', '3'])
+            .map(|c| c.to_digit(10).unwrap());
+        let block =
+            (!context.single('{') + stmt.parser().many_max(usize::MAX) + !context.single('}'))
+                .map(|v| v.iter().sum());
+        g.define(expr, digit | block);
+        g.define(stmt, expr.parser() + !context.single(';'));
+        let chars = "{1;{2;3;};}".chars().collect::<Vec<_>>();
+        assert_eq!(expr.parser().parse(&chars).unwrap(), 6);
+        let chars = "{1;{2;}".chars().collect::<Vec<_>>();
+        assert!(expr.parser().parse(&chars).is_err());
+    }
+
+    #[test]
+    fn left_recursive_rule_grows_seed() {
+        #[derive(Debug, Clone, PartialEq)]
+        enum Expr {
+            Num(u32),
+            Add(Box<Expr>, Box<Expr>),
+        }
+        let context = ParserContext::new();
+        let mut g = context.grammar::<Vec<char>>();
+        let expr = g.left_recursive_rule::<Expr>();
+        let term = context
+            .one_of_tokens(&['1', '2', '3'])
+            .map(|c| Expr::Num(c.to_digit(10).unwrap()));
+        let sum = (expr.parser() + !context.single('+') + term)
+            .map(|(l, r)| Expr::Add(Box::new(l), Box::new(r)));
+        g.define(expr, sum | term);
+        let chars = "1+2+3".chars().collect::<Vec<_>>();
+        let num = |n| Box::new(Expr::Num(n));
+        assert_eq!(
+            expr.parser().parse(&chars).unwrap(),
+            Expr::Add(Box::new(Expr::Add(num(1), num(2))), num(3))
+        );
+    }
+
+    #[test]
+    fn left_recursive_rule_keeps_inner_errors() {
+        let context = ParserContext::new();
+        let mut g = context.grammar::<Vec<char>>();
+        let atom = g.left_recursive_rule::<char>();
+        let expr = g.left_recursive_rule::<char>();
+        let a = context.single('a').label("an operand");
+        let chain = (expr.parser() + context.single('b').cut()).map(|(_, b)| b);
+        g.define(atom, a);
+        g.define(expr, chain | a);
+        let error = atom.parser().parse(&vec!['x']).unwrap_err();
+        assert_eq!(error.to_string(), "an operand");
+        let error = expr.parser().parse(&vec!['a', 'c']).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Hard);
+    }
+
+    #[test]
+    fn declare_builds_nested_parentheses() {
+        let context = ParserContext::new();
+        let (nested, declaration) = context.declare::<Vec<char>, usize>();
+        let undefined = nested.parse(&vec!['(', ')']).unwrap_err();
+        assert_eq!(undefined.to_string(), "undefined grammar rule");
+        declaration
+            .define((!context.single('(') + nested + !context.single(')')).map_or(0, |d| d + 1));
+        let chars = "((()))".chars().collect::<Vec<_>>();
+        assert_eq!(nested.parse(&chars).unwrap(), 3);
+    }
+
+    #[test]
+    fn recursive_supports_mutual_recursion() {
+        let context = ParserContext::new();
+        let list = context.recursive(|list| {
+            let item = context.recursive(|item| {
+                (!context.single('[') + item.many0() + !context.single(']')).map(|v| v.len() + 1)
+                    | (!context.single('<') + list + !context.single('>'))
+            });
+            item.many0().map(|v| v.iter().sum::<usize>())
+        });
+        let chars = "[[]]<[][]>".chars().collect::<Vec<_>>();
+        assert_eq!(list.parse(&chars).unwrap(), 4);
+    }
+}
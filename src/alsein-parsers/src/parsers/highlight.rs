@@ -0,0 +1,58 @@
+use std::{ops::Range, sync::atomic::Ordering};
+
+use super::{Parser, ParserContext, RawParser, Set};
+
+impl ParserContext<'_> {
+    pub fn token_map(&self) -> Vec<(String, Range<usize>, usize)> {
+        let mut tokens = self.diagnostics.lock().unwrap().tokens.clone();
+        tokens.sort_by_key(|(_, range, depth)| (range.start, *depth));
+        tokens
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R> {
+    pub fn highlight(
+        self,
+        name: &str,
+    ) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        let name = name.to_string();
+        self.context.new_parser(move |input: &I, start| {
+            let depth = self.context.token_depth.fetch_add(1, Ordering::Relaxed);
+            let result = self.run(input, start);
+            self.context.token_depth.store(depth, Ordering::Relaxed);
+            let (output, end) = result?;
+            self.context
+                .diagnostics
+                .lock()
+                .unwrap()
+                .tokens
+                .push((name.clone(), start..end, depth));
+            Ok((output, end))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::*;
+
+    #[test]
+    fn token_map_lists_nested_highlights() {
+        let chars = "(ab)".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let ident = context.chars_while(char::is_alphabetic).highlight("ident");
+        let open = context.single('(').highlight("punct");
+        let close = context.single(')').highlight("punct");
+        let group = (open + ident + close).highlight("group");
+        group.parse(&chars).unwrap();
+        assert_eq!(
+            context.token_map(),
+            vec![
+                ("group".to_string(), 0..4, 0),
+                ("punct".to_string(), 0..1, 1),
+                ("ident".to_string(), 1..3, 1),
+                ("punct".to_string(), 3..4, 1),
+            ]
+        );
+    }
+}
@@ -0,0 +1,68 @@
+use super::Error;
+
+#[cfg(feature = "anyhow")]
+impl Error {
+    pub fn into_anyhow(self) -> anyhow::Error {
+        anyhow::Error::new(self)
+    }
+}
+
+#[cfg(feature = "miette")]
+fn first_hint(error: &Error) -> Option<&str> {
+    match error {
+        Error::Hinted(_, hint) => Some(hint),
+        Error::StaticHint(_, hint) => Some(hint),
+        Error::Add(l) | Error::Or(l) => l.iter().find_map(first_hint),
+        Error::Fatal(inner) => first_hint(inner),
+        Error::Single(..) | Error::Succeed(_) | Error::Incomplete(_) | Error::Trailing(_) => None,
+    }
+}
+
+#[cfg(feature = "miette")]
+impl miette::Diagnostic for Error {
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let range = self.range();
+        let label = first_hint(self).map(str::to_string);
+        Some(Box::new(std::iter::once(miette::LabeledSpan::new(
+            label,
+            range.start,
+            range.len(),
+        ))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::*;
+
+    #[cfg(feature = "anyhow")]
+    #[test]
+    fn into_anyhow_keeps_message() {
+        let error = Error::Hinted(
+            Box::new(Error::Single(0.0, 1)),
+            "expected digit".to_string(),
+        );
+        assert_eq!(error.into_anyhow().to_string(), "expected digit");
+    }
+
+    #[cfg(feature = "miette")]
+    #[test]
+    fn miette_report_labels_range() {
+        use miette::Diagnostic;
+
+        let chars = "ax".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let digit = context.single('1').label("expected digit");
+        let error = (!context.single('a') + digit).parse(&chars).unwrap_err();
+        let labels = error.labels().unwrap().collect::<Vec<_>>();
+        assert_eq!(labels[0].offset(), 0);
+        assert_eq!(labels[0].len(), 2);
+        let report = miette::Report::new(error).with_source_code("ax");
+        let mut rendered = String::new();
+        miette::NarratableReportHandler::new()
+            .render_report(&mut rendered, report.as_ref())
+            .unwrap();
+        assert!(rendered.contains("snippet line 1: ax"));
+        assert!(rendered.contains("label at line 1, columns 1 to 2: expected digit"));
+    }
+}
@@ -0,0 +1,99 @@
+use super::{Error, Matcher, Parser, ParserContext, RawParser, Set};
+
+impl<'a> ParserContext<'a> {
+    pub fn not<I: Set + ?Sized, R: RawParser<I> + ?Sized + 'a>(
+        &'a self,
+        parser: Parser<'a, I, R>,
+    ) -> Matcher<'a, I, impl RawParser<I, Output = ()> + 'a> {
+        Matcher(self.new_parser(move |input: &I, start| {
+            let snapshot = self.snapshot();
+            let result = parser.run(input, start);
+            self.restore(snapshot);
+            match result {
+                Ok((_, end)) => Err(Error::Succeed(start..end) + Error::Single(0.0, start)),
+                Err(_) => Ok(((), start)),
+            }
+        }))
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R> {
+    pub fn peek(self) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            let (result, _) = self.run(input, start)?;
+            Ok((result, start))
+        })
+    }
+
+    pub fn followed_by<R2: RawParser<I, Output = ()> + ?Sized + 'a>(
+        self,
+        look: Matcher<'a, I, R2>,
+    ) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            let (result, end) = self.run(input, start)?;
+            match look.0.run(input, end) {
+                Ok(_) => Ok((result, end)),
+                Err(e) => Err(Error::Succeed(start..end) + e),
+            }
+        })
+    }
+
+    pub fn not_followed_by<R2: RawParser<I, Output = ()> + ?Sized + 'a>(
+        self,
+        look: Matcher<'a, I, R2>,
+    ) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            let (result, end) = self.run(input, start)?;
+            match look.0.run(input, end) {
+                Ok(_) => Err(Error::Succeed(start..end) + Error::Single(0.0, end)),
+                Err(_) => Ok((result, end)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::*;
+
+    #[test]
+    fn followed_by_requires_context() {
+        let context = ParserContext::new();
+        let percent = !context.single('%');
+        let amount = context.single('5').followed_by(percent);
+        assert_eq!(amount.parse(&vec!['5', '%']).unwrap(), '5');
+        assert_eq!((amount + percent).parse(&vec!['5', '%']).unwrap(), '5');
+        assert_eq!(amount.parse(&vec!['5']).unwrap_err().range(), 0..2);
+    }
+
+    #[test]
+    fn not_followed_by_rejects_trailing_context() {
+        let context = ParserContext::new();
+        let int = context
+            .chars_while(|c| c.is_ascii_digit())
+            .not_followed_by(!context.single('.'));
+        let parse = |s: &str| int.parse(&s.chars().collect::<Vec<_>>());
+        assert_eq!(parse("314").unwrap(), 0..3);
+        assert_eq!(parse("3.14").unwrap_err().range(), 0..2);
+    }
+
+    #[test]
+    fn peek_does_not_consume() {
+        let chars = "ab".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let a = context.single('a');
+        assert_eq!((a.peek() + a).parse(&chars).unwrap(), ('a', 'a'));
+        assert!(context.single('b').peek().parse(&chars).is_err());
+    }
+
+    #[test]
+    fn not_separates_keywords_from_identifiers() {
+        let context = ParserContext::new();
+        let letter = context
+            .chars_while(char::is_alphanumeric)
+            .require_progress();
+        let keyword = context.str_tag("if") + context.not(letter);
+        assert!(keyword.parse(&"if".chars().collect::<Vec<_>>()).is_ok());
+        assert!(keyword.parse(&"iffy".chars().collect::<Vec<_>>()).is_err());
+    }
+}
@@ -0,0 +1,90 @@
+use super::{Error, Matcher, ModeChange, Parser, ParserContext, RawParser, Set};
+
+impl<'a> ParserContext<'a> {
+    pub fn current_mode(&self) -> Option<&'static str> {
+        self.diagnostics.lock().unwrap().modes.last().copied()
+    }
+
+    pub fn push_mode<I: Set + ?Sized>(
+        &'a self,
+        mode: &'static str,
+    ) -> Matcher<'a, I, impl RawParser<I, Output = ()> + 'a> {
+        Matcher(self.new_parser(move |_: &I, start| {
+            let mut diagnostics = self.diagnostics.lock().unwrap();
+            diagnostics.modes.push(mode);
+            diagnostics.mode_changes.push(ModeChange::Pushed);
+            Ok(((), start))
+        }))
+    }
+
+    pub fn pop_mode<I: Set + ?Sized>(
+        &'a self,
+    ) -> Matcher<'a, I, impl RawParser<I, Output = ()> + 'a> {
+        Matcher(self.new_parser(move |_: &I, start| {
+            let mut diagnostics = self.diagnostics.lock().unwrap();
+            match diagnostics.modes.pop() {
+                Some(mode) => {
+                    diagnostics.mode_changes.push(ModeChange::Popped(mode));
+                    Ok(((), start))
+                }
+                None => Err(Error::Single(0.0, start)),
+            }
+        }))
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R> {
+    pub fn in_mode(
+        self,
+        mode: &'static str,
+    ) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            if self.context.current_mode() == Some(mode) {
+                self.run(input, start)
+            } else {
+                Err(Error::Single(0.0, start))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Token {
+        Word(char),
+        Quote,
+        Text(char),
+    }
+
+    #[test]
+    fn mode_stack_switches_lexer_rules() {
+        let chars = "ab\"c d\"e".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let text = context.none_of_str("\"").map(Token::Text).in_mode("string");
+        let close = (context.single('"') + context.pop_mode())
+            .map(|_| Token::Quote)
+            .in_mode("string");
+        let open = (context.single('"') + context.push_mode("string")).map(|_| Token::Quote);
+        let word = context
+            .one_of_tokens(&"abcde".chars().collect::<Vec<_>>())
+            .map(Token::Word);
+        let lexer = (text | close | open | word).many_max(usize::MAX);
+        assert_eq!(
+            lexer.parse(&chars).unwrap(),
+            vec![
+                Token::Word('a'),
+                Token::Word('b'),
+                Token::Quote,
+                Token::Text('c'),
+                Token::Text(' '),
+                Token::Text('d'),
+                Token::Quote,
+                Token::Word('e'),
+            ]
+        );
+        assert_eq!(context.current_mode(), None);
+    }
+}
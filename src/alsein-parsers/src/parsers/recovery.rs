@@ -0,0 +1,435 @@
+use std::{any::TypeId, marker::PhantomData};
+
+use super::{
+    Error, ErrorKind, Matcher, MatcherSyncDyn, ModeChange, Parser, ParserContext, RawParser, Set,
+};
+
+type RawMatcher<'a, I> = &'a (dyn RawParser<I, Output = ()> + Send + Sync + 'a);
+
+#[derive(Debug, Clone, Copy)]
+pub struct DiagSnapshot {
+    errors: usize,
+    warnings: usize,
+    mode_changes: usize,
+    tokens: usize,
+    notes: usize,
+    emitted: usize,
+    deferred: usize,
+}
+
+impl<'a> ParserContext<'a> {
+    pub fn set_sync_tokens<I: Set + ?Sized>(&'a self, tokens: Vec<MatcherSyncDyn<'a, I>>) {
+        let tokens: &'a Vec<RawMatcher<'a, I>> = self
+            .pool
+            .add(Box::new(tokens.iter().map(|t| t.0.raw).collect()));
+        *self.sync_tokens.lock().unwrap() = Some((TypeId::of::<I>(), tokens));
+    }
+
+    pub fn sync_tokens<I: Set + ?Sized>(&'a self) -> Vec<MatcherSyncDyn<'a, I>> {
+        match *self.sync_tokens.lock().unwrap() {
+            Some((input, tokens)) if input == TypeId::of::<I>() => {
+                let tokens = unsafe { &*(tokens as *const _ as *const Vec<RawMatcher<'a, I>>) };
+                tokens
+                    .iter()
+                    .map(|&raw| {
+                        Matcher(Parser {
+                            raw,
+                            context: self,
+                            _phantom: PhantomData,
+                        })
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn report(&self, error: Error) {
+        self.diagnostics.lock().unwrap().errors.push(error);
+    }
+
+    pub fn take_errors(&self) -> Vec<Error> {
+        std::mem::take(&mut self.diagnostics.lock().unwrap().errors)
+    }
+
+    pub fn warn(&self, warning: String) {
+        self.diagnostics.lock().unwrap().warnings.push(warning);
+    }
+
+    pub fn take_warnings(&self) -> Vec<String> {
+        std::mem::take(&mut self.diagnostics.lock().unwrap().warnings)
+    }
+
+    pub fn notes(&self) -> Vec<(&'static str, usize)> {
+        self.diagnostics.lock().unwrap().notes.clone()
+    }
+
+    pub fn snapshot(&self) -> DiagSnapshot {
+        let diagnostics = self.diagnostics.lock().unwrap();
+        DiagSnapshot {
+            errors: Vec::len(&diagnostics.errors),
+            warnings: Vec::len(&diagnostics.warnings),
+            mode_changes: Vec::len(&diagnostics.mode_changes),
+            tokens: Vec::len(&diagnostics.tokens),
+            notes: Vec::len(&diagnostics.notes),
+            emitted: Vec::len(&diagnostics.emitted),
+            deferred: Vec::len(&diagnostics.deferred),
+        }
+    }
+
+    pub fn restore(&self, snapshot: DiagSnapshot) {
+        let diagnostics = &mut *self.diagnostics.lock().unwrap();
+        diagnostics.errors.truncate(snapshot.errors);
+        diagnostics.warnings.truncate(snapshot.warnings);
+        while Vec::len(&diagnostics.mode_changes) > snapshot.mode_changes {
+            match diagnostics.mode_changes.pop() {
+                Some(ModeChange::Pushed) => {
+                    diagnostics.modes.pop();
+                }
+                Some(ModeChange::Popped(mode)) => diagnostics.modes.push(mode),
+                None => {}
+            }
+        }
+        diagnostics.tokens.truncate(snapshot.tokens);
+        diagnostics.notes.truncate(snapshot.notes);
+        diagnostics.emitted.truncate(snapshot.emitted);
+        diagnostics.deferred.truncate(snapshot.deferred);
+    }
+
+    fn sync_position<I: Set + ?Sized>(&'a self, input: &I, start: usize) -> usize {
+        let tokens = self.sync_tokens::<I>();
+        (start..input.len())
+            .find(|&pos| tokens.iter().any(|t| t.0.run(input, pos).is_ok()))
+            .unwrap_or_else(|| input.len())
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R>
+where
+    R::Output: Clone + 'a,
+{
+    pub fn recover(
+        self,
+        placeholder: R::Output,
+    ) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.context
+            .new_parser(move |input: &I, start| match self.run(input, start) {
+                Ok(r) => Ok(r),
+                Err(e) => {
+                    let end = self.context.sync_position(input, start);
+                    self.context.report(e);
+                    Ok((placeholder.clone(), end))
+                }
+            })
+    }
+
+    pub fn recover_balanced<
+        R1: RawParser<I, Output = ()> + ?Sized + 'a,
+        R2: RawParser<I, Output = ()> + ?Sized + 'a,
+    >(
+        self,
+        open: Matcher<'a, I, R1>,
+        close: Matcher<'a, I, R2>,
+        placeholder: R::Output,
+    ) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.context
+            .new_parser(move |input: &I, start| match self.run(input, start) {
+                Ok(r) => Ok(r),
+                Err(e) => {
+                    let mut depth = 0usize;
+                    let mut pos = start;
+                    while pos < input.len() {
+                        if let Ok((_, end)) = open.0.run(input, pos) {
+                            depth += 1;
+                            pos = end.max(pos + 1);
+                        } else if let Ok((_, end)) = close.0.run(input, pos) {
+                            if depth == 0 {
+                                break;
+                            }
+                            depth -= 1;
+                            pos = end.max(pos + 1);
+                        } else {
+                            pos += 1;
+                        }
+                    }
+                    self.context.report(e);
+                    Ok((placeholder.clone(), pos))
+                }
+            })
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R> {
+    pub fn skip_until<R2: RawParser<I, Output = ()> + ?Sized + 'a>(
+        self,
+        sync: Matcher<'a, I, R2>,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Option<R::Output>> + 'a> {
+        self.context
+            .new_parser(move |input: &I, start| match self.run(input, start) {
+                Ok((result, end)) => Ok((Some(result), end)),
+                Err(e) if e.kind() == ErrorKind::Hard => Err(e),
+                Err(_) => {
+                    let end = (start..input.len())
+                        .find(|&pos| sync.0.run(input, pos).is_ok())
+                        .unwrap_or_else(|| input.len());
+                    Ok((None, end))
+                }
+            })
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R> {
+    pub fn or_else_ctx<R2: RawParser<I, Output = R::Output> + ?Sized + 'a>(
+        self,
+        f: impl Fn(Error) -> Parser<'a, I, R2> + 'a,
+    ) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            let snapshot = self.context.snapshot();
+            match self.run(input, start) {
+                Ok(r) => Ok(r),
+                Err(e1) if e1.kind() == ErrorKind::Hard => Err(e1),
+                Err(e1) => {
+                    self.context.restore(snapshot);
+                    match f(e1.clone()).run(input, start) {
+                        Ok(r) => Ok(r),
+                        Err(e2) => Err(e1 | e2),
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R> {
+    pub fn opt_tracked(
+        self,
+        tag: &'static str,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Option<R::Output>> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            let snapshot = self.context.snapshot();
+            match self.run(input, start) {
+                Ok((result, end)) => Ok((Some(result), end)),
+                Err(e) if e.kind() == ErrorKind::Hard => Err(e),
+                Err(_) => {
+                    self.context.restore(snapshot);
+                    self.context
+                        .diagnostics
+                        .lock()
+                        .unwrap()
+                        .notes
+                        .push((tag, start));
+                    Ok((None, start))
+                }
+            }
+        })
+    }
+
+    pub fn parse_resilient(&self, input: &I) -> (Option<R::Output>, Vec<Error>) {
+        self.context.take_errors();
+        let output = match self.run_top_level(input) {
+            Ok((result, end)) => {
+                if end < input.len() {
+                    self.context.report(Error::Single(0.0, end));
+                }
+                Some(result)
+            }
+            Err(e) => {
+                self.context.report(e);
+                None
+            }
+        };
+        (output, self.context.take_errors())
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I, Output = ()> + ?Sized> Matcher<'a, I, R> {
+    pub fn or_insert(self, err_msg: &str) -> Matcher<'a, I, impl RawParser<I, Output = ()> + 'a> {
+        let err_msg = err_msg.to_string();
+        Matcher(
+            self.0
+                .context
+                .new_parser(move |input: &I, start| match self.0.run(input, start) {
+                    Ok(r) => Ok(r),
+                    Err(e) => {
+                        self.0
+                            .context
+                            .report(Error::Hinted(Box::new(e), err_msg.clone()));
+                        Ok(((), start))
+                    }
+                }),
+        )
+    }
+}
+
+impl<'a, I: Set, R: RawParser<I> + ?Sized> Parser<'a, I, R> {
+    pub fn or_transformed(
+        self,
+        transform: impl Fn(&I) -> (I, Vec<usize>) + 'a,
+    ) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.context
+            .new_parser(move |input: &I, start| match self.run(input, start) {
+                Ok(r) => Ok(r),
+                Err(e) => {
+                    let (transformed, positions) = transform(input);
+                    let start = positions.iter().position(|&pos| pos >= start);
+                    match start.map(|start| self.run(&transformed, start)) {
+                        Some(Ok((result, end))) if end < positions.len() => {
+                            Ok((result, positions[end]))
+                        }
+                        Some(Ok((result, _))) => Ok((result, input.len())),
+                        _ => Err(e),
+                    }
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::*;
+
+    #[test]
+    fn recover_skips_to_first_sync_token() {
+        let chars = "xy;}".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        context.set_sync_tokens::<Vec<char>>(vec![
+            (!context.single('}')).into_dyn_sync(),
+            (!context.single(';')).into_dyn_sync(),
+        ]);
+        let statement = context.single('a').recover('?') + context.single(';');
+        assert_eq!(statement.parse(&chars).unwrap(), ('?', ';'));
+        assert_eq!(context.take_errors().len(), 1);
+    }
+
+    #[test]
+    fn skip_until_resynchronizes() {
+        let chars = "xy;a;".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let semicolon = !context.single(';');
+        let statement = context.single('a').skip_until(semicolon) + semicolon;
+        let statements = statement + statement;
+        assert_eq!(statements.parse(&chars).unwrap(), (None, Some('a')));
+        assert!(context.take_errors().is_empty());
+    }
+
+    #[test]
+    fn or_transformed_retries_on_stripped_input() {
+        let chars = "1_0;".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let digits = context.new_parser(|input: &Vec<char>, start| {
+            let end = (start..input.len())
+                .find(|&i| !input[i].is_ascii_digit())
+                .unwrap_or(input.len());
+            let value = input[start..end].iter().collect::<String>();
+            Ok((value.parse::<u32>().unwrap_or(0), end))
+        });
+        let strip = |input: &Vec<char>| {
+            let positions = (0..input.len())
+                .filter(|&i| input[i] != '_')
+                .collect::<Vec<_>>();
+            (positions.iter().map(|&i| input[i]).collect(), positions)
+        };
+        let statement = (digits + !context.single(';')).or_transformed(strip);
+        assert_eq!(statement.parse(&chars).unwrap(), 10);
+        assert!((digits + !context.single(';')).parse(&chars).is_err());
+    }
+
+    #[test]
+    fn failed_branch_diagnostics_are_rolled_back() {
+        let chars = "b".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let noisy = context.new_parser(|_: &Vec<char>, start| {
+            context.warn("speculative".to_string());
+            Err(Error::Single(0.0, start))
+        });
+        let choice = noisy | context.single('b');
+        assert_eq!(choice.parse(&chars).unwrap(), 'b');
+        assert!(context.take_warnings().is_empty());
+        let snapshot = context.snapshot();
+        context.warn("kept".to_string());
+        context.report(Error::Single(0.0, 0));
+        context.restore(snapshot);
+        assert!(context.take_warnings().is_empty());
+        assert!(context.take_errors().is_empty());
+    }
+
+    #[test]
+    fn restore_replays_mode_changes() {
+        let chars = "ab".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let popped = context.pop_mode() + context.single('x');
+        let grammar = context.push_mode("outer") + (popped | context.single('a'));
+        assert_eq!(grammar.parse(&chars).unwrap(), 'a');
+        assert_eq!(context.current_mode(), Some("outer"));
+    }
+
+    #[test]
+    fn or_else_ctx_resumes_at_first_error() {
+        let chars = "a?b".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let primary = !context.single('a') + context.single('b');
+        let resilient = primary.or_else_ctx(|e| {
+            let end = e.range().end;
+            let jump = context.new_parser(move |_: &Vec<char>, _| Ok(((), end)));
+            !jump + context.single('b')
+        });
+        assert!(primary.parse(&chars).is_err());
+        assert_eq!(resilient.parse(&chars).unwrap(), 'b');
+    }
+
+    #[test]
+    fn parse_resilient_collects_independent_errors() {
+        let chars = "1;x;2;y;z;".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        context.set_sync_tokens::<Vec<char>>(vec![(!context.single(';')).into_dyn_sync()]);
+        let digit = context.one_of_tokens(&['0', '1', '2']).recover('?');
+        let statements = (digit + !context.single(';')).many_max(usize::MAX);
+        let (tree, errors) = statements.parse_resilient(&chars);
+        assert_eq!(tree, Some(vec!['1', '?', '2', '?', '?']));
+        assert_eq!(errors.len(), 3);
+        let (tree, errors) = statements.parse_resilient(&"%%".chars().collect());
+        assert_eq!(tree, Some(vec![]));
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn recover_balanced_skips_nested_blocks() {
+        let chars = "{?{a}b}".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let body =
+            context
+                .single('a')
+                .recover_balanced(!context.single('{'), !context.single('}'), '?');
+        let block = !context.single('{') + body + !context.single('}');
+        assert_eq!(block.parse(&chars).unwrap(), '?');
+        assert_eq!(context.take_errors().len(), 1);
+    }
+
+    #[test]
+    fn opt_tracked_notes_missing_constructs() {
+        let chars = "x:ay".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let annotation = !context.single(':') + context.single('a');
+        let name = context.one_of_tokens(&['x', 'y']);
+        let binding = name + annotation.opt_tracked("type annotation");
+        let bindings = binding + binding;
+        assert_eq!(
+            bindings.parse(&chars).unwrap(),
+            (('x', Some('a')), ('y', None))
+        );
+        assert_eq!(context.notes(), vec![("type annotation", 4)]);
+    }
+
+    #[test]
+    fn or_insert_virtually_inserts_missing_token() {
+        let chars = "(a".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let close = (!context.single(')')).or_insert("missing ')'");
+        let group = !context.single('(') + context.single('a') + close;
+        assert_eq!(group.parse(&chars).unwrap(), 'a');
+        let errors = context.take_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].to_string(), "missing ')'");
+        assert_eq!(errors[0].range(), 2..3);
+    }
+}
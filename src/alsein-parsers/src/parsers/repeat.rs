@@ -0,0 +1,795 @@
+use std::{collections::HashSet, hash::Hash, ops::Range};
+
+use super::{
+    Error, ErrorKind, Matcher, Parser, ParserContext, ParserResult, RawParser, Result, Set,
+};
+
+type Until<O, E> = (Vec<O>, E);
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R> {
+    pub fn require_progress(self) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            let (result, end) = self.run(input, start)?;
+            if end == start {
+                Err(Error::Hinted(
+                    Box::new(Error::Single(0.0, start)),
+                    "parser matched without consuming input".to_string(),
+                ))
+            } else {
+                Ok((result, end))
+            }
+        })
+    }
+
+    pub fn many0_progress(
+        self,
+        cb: impl Fn(usize) + 'a,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Vec<R::Output>> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            self.context.repeat(
+                start,
+                Vec::new(),
+                |pos| self.run(input, pos),
+                |results, result, span| {
+                    results.push(result);
+                    cb(span.end);
+                    Ok(())
+                },
+            )
+        })
+    }
+
+    pub fn many_max(
+        self,
+        max: usize,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Vec<R::Output>> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            self.context.repeat(
+                start,
+                Vec::new(),
+                |pos| self.run(input, pos),
+                |results, result, span| {
+                    if Vec::len(results) == max {
+                        return Err(Error::Hinted(
+                            Box::new(Error::Single(0.0, span.start)),
+                            "too many".to_string(),
+                        ));
+                    }
+                    results.push(result);
+                    Ok(())
+                },
+            )
+        })
+    }
+
+    pub fn many0_with_capacity(
+        self,
+        cap: usize,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Vec<R::Output>> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            self.context.repeat(
+                start,
+                Vec::with_capacity(cap),
+                |pos| self.run(input, pos),
+                |results, result, _| {
+                    results.push(result);
+                    Ok(())
+                },
+            )
+        })
+    }
+
+    pub fn chunks(
+        self,
+        record_len: usize,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Vec<R::Output>> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            let len = input.len().saturating_sub(start);
+            if record_len == 0 || len % record_len != 0 {
+                return Err(Error::Hinted(
+                    Box::new(Error::Single(0.0, input.len())),
+                    format!("input length {} is not a multiple of {}", len, record_len),
+                ));
+            }
+            let mut results = Vec::with_capacity(len / record_len);
+            for pos in (start..input.len()).step_by(record_len) {
+                let (result, end) = self.run(input, pos)?;
+                if end != pos + record_len {
+                    return Err(Error::Hinted(
+                        Box::new(Error::Single(0.0, end.min(pos + record_len))),
+                        format!("record consumed {} of {} elements", end - pos, record_len),
+                    ));
+                }
+                results.push(result);
+            }
+            Ok((results, input.len()))
+        })
+    }
+
+    pub fn repeated_until<R2: RawParser<I> + ?Sized + 'a>(
+        self,
+        end: Parser<'a, I, R2>,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Until<R::Output, R2::Output>> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            let mut results = Vec::new();
+            let mut pos = start;
+            loop {
+                let snapshot = self.context.snapshot();
+                let end_error = match end.run(input, pos) {
+                    Ok((terminator, end)) => return Ok(((results, terminator), end)),
+                    Err(e) if e.kind() == ErrorKind::Hard => return Err(e),
+                    Err(e) => e,
+                };
+                self.context.restore(snapshot);
+                let error = match self.run(input, pos) {
+                    Ok((result, next)) if next > pos => {
+                        results.push(result);
+                        pos = next;
+                        continue;
+                    }
+                    Ok(_) => end_error,
+                    Err(e) if e.kind() == ErrorKind::Hard => return Err(e),
+                    Err(e) => end_error | e,
+                };
+                return Err(if pos > start {
+                    Error::Succeed(start..pos) + error
+                } else {
+                    error
+                });
+            }
+        })
+    }
+
+    fn separated<R2: RawParser<I, Output = ()> + ?Sized + 'a>(
+        self,
+        delim: Matcher<'a, I, R2>,
+        at_least_one: bool,
+        trailing: bool,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Vec<R::Output>> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            let snapshot = self.context.snapshot();
+            let (first, pos) = match self.run(input, start) {
+                Ok(r) => r,
+                Err(e) if at_least_one => return Err(e),
+                Err(_) => {
+                    self.context.restore(snapshot);
+                    return Ok((Vec::new(), start));
+                }
+            };
+            let (results, pos) = self.context.repeat(
+                pos,
+                vec![first],
+                |pos| {
+                    let (_, after_delim) = delim.0.run(input, pos)?;
+                    self.run(input, after_delim)
+                },
+                |results, result, _| {
+                    results.push(result);
+                    Ok(())
+                },
+            )?;
+            if !trailing {
+                return Ok((results, pos));
+            }
+            match self.context.attempt(pos, || delim.0.run(input, pos))? {
+                Some((_, end)) => Ok((results, end)),
+                None => Ok((results, pos)),
+            }
+        })
+    }
+
+    pub fn separated_by<R2: RawParser<I, Output = ()> + ?Sized + 'a>(
+        self,
+        delim: Matcher<'a, I, R2>,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Vec<R::Output>> + 'a> {
+        self.separated(delim, false, false)
+    }
+
+    pub fn separated_by1<R2: RawParser<I, Output = ()> + ?Sized + 'a>(
+        self,
+        delim: Matcher<'a, I, R2>,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Vec<R::Output>> + 'a> {
+        self.separated(delim, true, false)
+    }
+
+    pub fn separated_by_trailing<R2: RawParser<I, Output = ()> + ?Sized + 'a>(
+        self,
+        delim: Matcher<'a, I, R2>,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Vec<R::Output>> + 'a> {
+        self.separated(delim, false, true)
+    }
+
+    pub fn repeat_diagnosed(
+        self,
+        n: usize,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Vec<Result<R::Output>>> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            let mut results = Vec::with_capacity(n);
+            let mut pos = start;
+            for _ in 0..n {
+                match self.run(input, pos) {
+                    Ok((result, end)) => {
+                        results.push(Ok(result));
+                        pos = end;
+                    }
+                    Err(e) if e.kind() == ErrorKind::Hard => return Err(e),
+                    Err(e) => {
+                        pos = e.range().end;
+                        results.push(Err(e));
+                    }
+                }
+            }
+            Ok((results, pos))
+        })
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R>
+where
+    R::Output: Eq + Hash + Clone,
+{
+    pub fn many_unique(self) -> Parser<'a, I, impl RawParser<I, Output = Vec<R::Output>> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            let mut seen = HashSet::new();
+            self.context.repeat(
+                start,
+                Vec::new(),
+                |pos| self.run(input, pos),
+                |results, result, span| {
+                    if !seen.insert(result.clone()) {
+                        return Err(Error::Hinted(
+                            Box::new(Error::Single(0.0, span.start)),
+                            "duplicate item".to_string(),
+                        ));
+                    }
+                    results.push(result);
+                    Ok(())
+                },
+            )
+        })
+    }
+}
+
+impl<'a, I: Set + ?Sized, K: PartialEq, V, R: RawParser<I, Output = (K, V)> + ?Sized>
+    Parser<'a, I, R>
+{
+    pub fn collect_ordered_map(
+        self,
+        reject_duplicates: bool,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Vec<(K, V)>> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            self.context.repeat(
+                start,
+                Vec::new(),
+                |pos| self.run(input, pos),
+                |entries: &mut Vec<(K, V)>, (key, value), span| {
+                    if reject_duplicates && entries.iter().any(|(k, _)| k == &key) {
+                        return Err(Error::Hinted(
+                            Box::new(Error::Single(0.0, span.start)),
+                            "duplicate key".to_string(),
+                        ));
+                    }
+                    entries.push((key, value));
+                    Ok(())
+                },
+            )
+        })
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R> {
+    pub fn windows2(
+        self,
+        f: impl Fn(&R::Output, &R::Output) -> std::result::Result<(), String> + 'a,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Vec<R::Output>> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            self.context.repeat(
+                start,
+                Vec::new(),
+                |pos| self.run(input, pos),
+                |results: &mut Vec<R::Output>, result, span| {
+                    if let Some(prev) = results.last() {
+                        if let Err(message) = f(prev, &result) {
+                            return Err(Error::Hinted(
+                                Box::new(Error::Single(0.0, span.start)),
+                                message,
+                            ));
+                        }
+                    }
+                    results.push(result);
+                    Ok(())
+                },
+            )
+        })
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R>
+where
+    R::Output: PartialEq + Clone,
+{
+    pub fn group_runs(
+        self,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Vec<(R::Output, usize)>> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            self.context.repeat(
+                start,
+                Vec::new(),
+                |pos| self.run(input, pos),
+                |runs: &mut Vec<(R::Output, usize)>, result, _| {
+                    match runs.last_mut() {
+                        Some((value, count)) if *value == result => *count += 1,
+                        _ => runs.push((result, 1)),
+                    }
+                    Ok(())
+                },
+            )
+        })
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R>
+where
+    R::Output: AsRef<str>,
+{
+    pub fn join(self, sep: &str) -> Parser<'a, I, impl RawParser<I, Output = String> + 'a> {
+        let sep = sep.to_string();
+        self.context.new_parser(move |input: &I, start| {
+            let (first, pos) = self.run(input, start)?;
+            self.context.repeat(
+                pos,
+                first.as_ref().to_string(),
+                |pos| self.run(input, pos),
+                |joined, item, _| {
+                    *joined += &sep;
+                    *joined += item.as_ref();
+                    Ok(())
+                },
+            )
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Either<A, B> {
+    Left(A),
+    Right(B),
+}
+
+impl<'a> ParserContext<'a> {
+    fn attempt<O>(
+        &self,
+        pos: usize,
+        run: impl FnOnce() -> ParserResult<O>,
+    ) -> Result<Option<(O, usize)>> {
+        let snapshot = self.snapshot();
+        match run() {
+            Ok((result, end)) if end > pos => Ok(Some((result, end))),
+            Err(e) if e.kind() == ErrorKind::Hard => Err(e),
+            _ => {
+                self.restore(snapshot);
+                Ok(None)
+            }
+        }
+    }
+
+    fn repeat<O, T>(
+        &self,
+        start: usize,
+        mut acc: T,
+        mut item: impl FnMut(usize) -> ParserResult<O>,
+        mut step: impl FnMut(&mut T, O, Range<usize>) -> Result<()>,
+    ) -> ParserResult<T> {
+        let mut pos = start;
+        while let Some((result, end)) = self.attempt(pos, || item(pos))? {
+            step(&mut acc, result, pos..end)?;
+            pos = end;
+        }
+        Ok((acc, pos))
+    }
+
+    pub fn interleave<
+        I: Set + ?Sized,
+        A,
+        B,
+        R1: RawParser<I, Output = A> + ?Sized + 'a,
+        R2: RawParser<I, Output = B> + ?Sized + 'a,
+    >(
+        &'a self,
+        a: Parser<'a, I, R1>,
+        b: Parser<'a, I, R2>,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Vec<Either<A, B>>> + 'a> {
+        self.new_parser(move |input: &I, start| {
+            let mut turn = 0;
+            self.repeat(
+                start,
+                Vec::new(),
+                |pos| {
+                    turn += 1;
+                    if turn % 2 == 1 {
+                        a.run(input, pos).map(|(r, end)| (Either::Left(r), end))
+                    } else {
+                        b.run(input, pos).map(|(r, end)| (Either::Right(r), end))
+                    }
+                },
+                |results, result, _| {
+                    results.push(result);
+                    Ok(())
+                },
+            )
+        })
+    }
+
+    pub fn separated_min_seps<
+        I: Set + ?Sized,
+        R1: RawParser<I> + ?Sized + 'a,
+        R2: RawParser<I, Output = ()> + ?Sized + 'a,
+    >(
+        &'a self,
+        item: Parser<'a, I, R1>,
+        sep: Matcher<'a, I, R2>,
+        min_seps: usize,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Vec<R1::Output>> + 'a> {
+        let items = item.separated_by1(sep);
+        self.new_parser(move |input: &I, start| {
+            let (results, pos) = items.run(input, start)?;
+            if results.len() - 1 < min_seps {
+                return Err(Error::Hinted(
+                    Box::new(Error::Single(0.0, pos)),
+                    format!(
+                        "expected at least {} separator{}",
+                        min_seps,
+                        if min_seps == 1 { "" } else { "s" }
+                    ),
+                ));
+            }
+            Ok((results, pos))
+        })
+    }
+
+    pub fn for_each_segment<
+        T: 'static,
+        R1: RawParser<[T], Output = ()> + ?Sized + 'a,
+        R2: RawParser<[T]> + ?Sized + 'a,
+    >(
+        &'a self,
+        sep: Matcher<'a, [T], R1>,
+        item: Parser<'a, [T], R2>,
+    ) -> Parser<'a, [T], impl RawParser<[T], Output = Vec<R2::Output>> + 'a> {
+        self.new_parser(move |input: &[T], start| {
+            let mut segments = Vec::new();
+            let mut segment_start = start;
+            let mut pos = start;
+            while pos < input.len() {
+                match sep.0.run(input, pos) {
+                    Ok((_, end)) if end > pos => {
+                        segments.push((segment_start, pos));
+                        segment_start = end;
+                        pos = end;
+                    }
+                    _ => pos += 1,
+                }
+            }
+            segments.push((segment_start, input.len()));
+            let mut results = Vec::new();
+            let mut errors = Vec::new();
+            for (from, to) in segments {
+                match item.run(&input[..to], from) {
+                    Ok((result, end)) if end == to => results.push(result),
+                    Ok((_, end)) => errors.push(Error::Single(0.0, end.min(to))),
+                    Err(e) => errors.push(e),
+                }
+            }
+            match errors.len() {
+                0 => Ok((results, input.len())),
+                1 => Err(errors.remove(0)),
+                _ => Err(Error::Add(errors)),
+            }
+        })
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R>
+where
+    R::Output: Send,
+{
+    pub fn par_many<B: Send + 'a>(
+        self,
+        map: impl Fn(R::Output) -> B + Sync + Send + 'a,
+        reduce: impl Fn(B, B) -> B + Sync + Send + 'a,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Option<B>> + 'a> {
+        use rayon::prelude::*;
+
+        self.context.new_parser(move |input: &I, start| {
+            let (results, pos) = self.context.repeat(
+                start,
+                Vec::new(),
+                |pos| self.run(input, pos),
+                |results, result, _| {
+                    results.push(result);
+                    Ok(())
+                },
+            )?;
+            Ok((results.into_par_iter().map(&map).reduce_with(&reduce), pos))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use crate::parsers::*;
+
+    #[test]
+    fn many0_progress_reports_each_item() {
+        let chars = "aaab".chars().collect::<Vec<_>>();
+        let positions = RefCell::new(Vec::new());
+        let context = ParserContext::new();
+        let items = context
+            .single('a')
+            .many0_progress(|pos| positions.borrow_mut().push(pos));
+        assert_eq!(items.parse(&chars).unwrap(), vec!['a', 'a', 'a']);
+        assert_eq!(*positions.borrow(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn failed_iterations_are_rolled_back() {
+        let chars = "a,a,".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let comma = !context.single(',').emit(|&c| Some(c));
+        let items = context.single('a').separated_by(comma);
+        assert_eq!(items.parse(&chars).unwrap(), vec!['a', 'a']);
+        assert_eq!(context.emitted::<char>(), vec![',']);
+    }
+
+    #[test]
+    fn cut_inside_many0_propagates() {
+        let chars = "abac".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let pairs = (context.single('a') + context.single('b').cut()).many0();
+        let error = pairs.parse(&chars).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Hard);
+        assert_eq!(error.range(), 2..4);
+    }
+
+    #[test]
+    fn many_unique_rejects_duplicates() {
+        let context = ParserContext::new();
+        let flags = (context.single('a') | context.single('b') | context.single('c')).many_unique();
+        let abc = "abc".chars().collect::<Vec<_>>();
+        assert_eq!(flags.parse(&abc).unwrap(), vec!['a', 'b', 'c']);
+        let aba = "aba".chars().collect::<Vec<_>>();
+        assert_eq!(flags.parse(&aba).unwrap_err().range(), 2..3);
+    }
+
+    #[test]
+    fn repeat_diagnosed_reports_each_slot() {
+        let chars = "a?a".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let slots = context
+            .single('a')
+            .repeat_diagnosed(3)
+            .parse(&chars)
+            .unwrap();
+        assert_eq!(
+            slots.iter().map(|r| r.is_ok()).collect::<Vec<_>>(),
+            vec![true, false, true]
+        );
+        assert_eq!(slots[1].as_ref().unwrap_err().range(), 1..2);
+    }
+
+    #[test]
+    fn many_max_errors_past_limit() {
+        let context = ParserContext::new();
+        let args = context.single('a').many_max(3);
+        let three = "aaa".chars().collect::<Vec<_>>();
+        assert_eq!(args.parse(&three).unwrap().len(), 3);
+        let four = "aaaa".chars().collect::<Vec<_>>();
+        let error = args.parse(&four).unwrap_err();
+        assert_eq!(error.to_string(), "too many");
+        assert_eq!(error.range(), 3..4);
+    }
+
+    #[test]
+    fn separated_min_seps_requires_separator() {
+        let context = ParserContext::new();
+        let segment = context.single('a') | context.single('b');
+        let path = context.separated_min_seps(segment, !context.single('/'), 1);
+        let parse = |s: &str| path.parse(&s.chars().collect::<Vec<_>>());
+        assert_eq!(parse("a/b").unwrap(), vec!['a', 'b']);
+        assert_eq!(
+            parse("a").unwrap_err().to_string(),
+            "expected at least 1 separator"
+        );
+        let path = context.separated_min_seps(segment, !context.single('/'), 2);
+        assert_eq!(
+            path.parse(&"a/b".chars().collect::<Vec<_>>())
+                .unwrap_err()
+                .to_string(),
+            "expected at least 2 separators"
+        );
+    }
+
+    #[test]
+    fn interleave_alternates_kinds() {
+        let chars = "abab".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let pairs = context.interleave(context.single('a'), context.single('b'));
+        assert_eq!(
+            pairs.parse(&chars).unwrap(),
+            vec![
+                Either::Left('a'),
+                Either::Right('b'),
+                Either::Left('a'),
+                Either::Right('b')
+            ]
+        );
+        let chars = "aab".chars().collect::<Vec<_>>();
+        assert_eq!(pairs.parse(&chars).unwrap(), vec![Either::Left('a')]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_many_sums_digits() {
+        let chars = vec!['1'; 1_000_000];
+        let context = ParserContext::new();
+        let digit = context.single('1') | context.single('2');
+        let sum = digit.par_many(|c| c.to_digit(10).unwrap() as u64, |x, y| x + y);
+        assert_eq!(sum.parse(&chars).unwrap(), Some(1_000_000));
+    }
+
+    #[test]
+    fn collect_ordered_map_preserves_order() {
+        let context = ParserContext::new();
+        let key = context.single('b') | context.single('a');
+        let entry = key + !context.single('=') + context.single('1');
+        let input = "b=1a=1b=1".chars().collect::<Vec<_>>();
+        assert_eq!(
+            entry.collect_ordered_map(false).parse(&input).unwrap(),
+            vec![('b', '1'), ('a', '1'), ('b', '1')]
+        );
+        let error = entry.collect_ordered_map(true).parse(&input).unwrap_err();
+        assert_eq!(error.to_string(), "duplicate key");
+        assert_eq!(error.range(), 6..7);
+    }
+
+    #[test]
+    fn join_concatenates_items_with_separator() {
+        let chars = "abc1".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let identifier = context
+            .one_of_tokens(&['a', 'b', 'c'])
+            .map(|c| c.to_string());
+        let (path, rest) = identifier.join(".").parse_partial(&chars).unwrap();
+        assert_eq!(path, "a.b.c");
+        assert_eq!(rest, &['1']);
+    }
+
+    #[test]
+    fn windows2_checks_adjacent_items() {
+        let context = ParserContext::new();
+        let ascending = context.one_of_tokens(&['1', '2', '3']).windows2(|a, b| {
+            if a < b {
+                Ok(())
+            } else {
+                Err(format!("{} does not follow {}", b, a))
+            }
+        });
+        let chars = "123".chars().collect::<Vec<_>>();
+        assert_eq!(ascending.parse(&chars).unwrap(), vec!['1', '2', '3']);
+        let chars = "132".chars().collect::<Vec<_>>();
+        let error = ascending.parse(&chars).unwrap_err();
+        assert_eq!(error.to_string(), "2 does not follow 3");
+        assert_eq!(error.range(), 2..3);
+    }
+
+    #[test]
+    fn group_runs_counts_equal_neighbours() {
+        let chars = "aaabb".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let runs = context.one_of_tokens(&['a', 'b']).group_runs();
+        assert_eq!(runs.parse(&chars).unwrap(), vec![('a', 3), ('b', 2)]);
+    }
+
+    #[test]
+    fn for_each_segment_requires_whole_segments() {
+        let context = ParserContext::new();
+        let digit = context
+            .one_of_tokens(&['1', '2', '3'])
+            .map(|c| c.to_digit(10).unwrap());
+        let records = context.for_each_segment(!context.single(';'), digit);
+        let chars = "1;2;3".chars().collect::<Vec<_>>();
+        assert_eq!(records.parse(&chars).unwrap(), vec![1, 2, 3]);
+        let chars = "1;2x;3".chars().collect::<Vec<_>>();
+        assert_eq!(records.parse(&chars).unwrap_err().range(), 3..4);
+        let fields = context.for_each_segment(!context.single(';'), context.chars_while(|_| true));
+        let chars = "ab;;c".chars().collect::<Vec<_>>();
+        assert_eq!(fields.parse(&chars).unwrap(), vec![0..2, 3..3, 4..5]);
+    }
+
+    #[test]
+    fn many0_with_capacity_matches_many_max() {
+        let chars = "aaab".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let a = context.single('a');
+        let reserved = a.many0_with_capacity(16).parse_partial(&chars).unwrap();
+        assert_eq!(
+            reserved,
+            a.many_max(usize::MAX).parse_partial(&chars).unwrap()
+        );
+        assert_eq!(reserved.1, &['b']);
+    }
+
+    #[test]
+    fn require_progress_rejects_empty_matches() {
+        let chars = "b".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let empty = context.marker().require_progress();
+        assert_eq!(
+            empty.parse(&chars).unwrap_err().to_string(),
+            "parser matched without consuming input"
+        );
+        let items = empty.many0() + context.single('b');
+        assert_eq!(items.parse(&chars).unwrap(), (vec![], 'b'));
+        assert_eq!(
+            empty.many1().parse(&chars).unwrap_err().to_string(),
+            "parser matched without consuming input"
+        );
+    }
+
+    #[test]
+    fn chunks_parse_fixed_size_records() {
+        let context = ParserContext::new();
+        let digit = context.one_of_tokens(&['1', '2', '3']);
+        let record = context.single('#') + digit + digit;
+        let records = record.chunks(3);
+        let chars = "#12#23#31".chars().collect::<Vec<_>>();
+        assert_eq!(
+            records.parse(&chars).unwrap(),
+            vec![(('#', '1'), '2'), (('#', '2'), '3'), (('#', '3'), '1')]
+        );
+        assert!(records.parse(&"#12#2".chars().collect()).is_err());
+        let short = (context.single('#') + digit).chunks(3);
+        assert_eq!(
+            short.parse(&chars).unwrap_err().to_string(),
+            "record consumed 2 of 3 elements"
+        );
+    }
+
+    #[test]
+    fn separated_by_variants() {
+        let chars = "a,b,;".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let item = context.one_of_tokens(&['a', 'b']);
+        let comma = !context.single(',');
+        let (items, rest) = item.separated_by(comma).parse_partial(&chars).unwrap();
+        assert_eq!((items, rest), (vec!['a', 'b'], &[',', ';'][..]));
+        let (_, rest) = item
+            .separated_by_trailing(comma)
+            .parse_partial(&chars)
+            .unwrap();
+        assert_eq!(rest, &[';']);
+        assert!(item
+            .separated_by(comma)
+            .parse(&chars[4..])
+            .unwrap()
+            .is_empty());
+        assert!(item.separated_by1(comma).parse(&chars[4..]).is_err());
+    }
+
+    #[test]
+    fn repeated_until_stops_at_terminator() {
+        let chars = "/*a*b*/x".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let comment = !context.str_tag("/*")
+            + context
+                .none_of_str("")
+                .repeated_until(context.str_tag("*/"));
+        let ((body, _), rest) = comment.parse_partial(&chars).unwrap();
+        assert_eq!(body, vec!['a', '*', 'b']);
+        assert_eq!(rest, &['x']);
+        assert!(comment.parse(&chars[..5]).is_err());
+    }
+}
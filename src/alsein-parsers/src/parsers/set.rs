@@ -1,8 +1,13 @@
-use std::ops::Deref;
+use std::{cell::RefCell, collections::HashMap, ops::Deref};
+
+use super::{Error, Result};
 
 pub trait Set: 'static {
     type Output;
     fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
     fn get(&self, idx: usize) -> &Self::Output;
 }
 
@@ -29,3 +34,89 @@ impl<S: Set + ?Sized, D: Deref<Target = S> + 'static> Set for D {
         self.deref().get(idx)
     }
 }
+
+const ASCII: [char; 128] = {
+    let mut table = ['\0'; 128];
+    let mut i = 0;
+    while i < 128 {
+        table[i] = i as u8 as char;
+        i += 1;
+    }
+    table
+};
+
+#[repr(transparent)]
+pub struct Ascii([u8]);
+
+impl Ascii {
+    pub fn new(bytes: &[u8]) -> Result<&Ascii> {
+        match bytes.iter().position(|b| !b.is_ascii()) {
+            Some(pos) => Err(Error::Hinted(
+                Box::new(Error::Single(0.0, pos)),
+                format!("non-ASCII byte {:#04x}", bytes[pos]),
+            )),
+            None => Ok(unsafe { &*(bytes as *const [u8] as *const Ascii) }),
+        }
+    }
+}
+
+impl Set for Ascii {
+    type Output = char;
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn get(&self, idx: usize) -> &Self::Output {
+        &ASCII[self.0[idx] as usize]
+    }
+}
+
+pub struct Normalized<S: Set + ?Sized, O> {
+    source: *const S,
+    normalize: fn(&S::Output) -> O,
+    mapped: RefCell<HashMap<usize, Box<O>>>,
+}
+
+impl<S: Set + ?Sized, O> Normalized<S, O> {
+    pub(crate) fn new(source: &S, normalize: fn(&S::Output) -> O) -> Self {
+        Normalized {
+            source,
+            normalize,
+            mapped: RefCell::default(),
+        }
+    }
+}
+
+impl<S: Set + ?Sized, O: 'static> Set for Normalized<S, O> {
+    type Output = O;
+
+    fn len(&self) -> usize {
+        unsafe { &*self.source }.len()
+    }
+
+    fn get(&self, idx: usize) -> &Self::Output {
+        if let Some(value) = self.mapped.borrow().get(&idx) {
+            return unsafe { &*(&**value as *const O) };
+        }
+        let value = Box::new((self.normalize)(unsafe { &*self.source }.get(idx)));
+        let mapped = &*value as *const O;
+        self.mapped.borrow_mut().insert(idx, value);
+        unsafe { &*mapped }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::*;
+
+    #[test]
+    fn ascii_bytes_run_char_grammars() {
+        let context = ParserContext::new();
+        let keyword = context.str_tag("let") + context.chars_while(char::is_whitespace);
+        let (_, spaces) = keyword.parse(Ascii::new(b"let  ").unwrap()).unwrap();
+        assert_eq!(spaces, 3..5);
+        let error = Ascii::new(&[b'l', 0xff]).err().unwrap();
+        assert_eq!(error.range(), 1..2);
+    }
+}
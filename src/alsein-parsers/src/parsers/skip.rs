@@ -0,0 +1,156 @@
+use std::{any::TypeId, collections::HashMap, marker::PhantomData};
+
+use super::{MatcherSyncDyn, Parser, ParserContext, RawParser, Set};
+
+type RawMatcher<'a, I> = &'a (dyn RawParser<I, Output = ()> + Send + Sync + 'a);
+
+#[derive(Default)]
+pub(crate) struct SkipCache {
+    root: (usize, usize),
+    positions: HashMap<usize, usize>,
+}
+
+impl SkipCache {
+    pub(crate) fn reset<I: Set + ?Sized>(&mut self, root: &I) {
+        self.root = (root as *const I as *const () as usize, root.len());
+        self.positions.clear();
+    }
+}
+
+impl<'a> ParserContext<'a> {
+    pub fn set_skipper<I: Set + ?Sized>(&'a self, skipper: MatcherSyncDyn<'a, I>) {
+        let skipper: &'a RawMatcher<'a, I> = self.pool.add(Box::new(skipper.0.raw));
+        *self.skipper.lock().unwrap() = Some((TypeId::of::<I>(), skipper));
+        self.skip_cache.lock().unwrap().positions.clear();
+    }
+
+    fn skipper<I: Set + ?Sized>(
+        &'a self,
+    ) -> Option<Parser<'a, I, dyn RawParser<I, Output = ()> + Send + Sync + 'a>> {
+        match *self.skipper.lock().unwrap() {
+            Some((input, skipper)) if input == TypeId::of::<I>() => Some(Parser {
+                raw: unsafe { *(skipper as *const _ as *const RawMatcher<'a, I>) },
+                context: self,
+                _phantom: PhantomData,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn skip<I: Set + ?Sized>(&'a self, input: &I, start: usize) -> usize {
+        let root = (input as *const I as *const () as usize, input.len());
+        let cached = {
+            let cache = self.skip_cache.lock().unwrap();
+            (cache.root == root).then(|| cache.positions.get(&start).copied())
+        };
+        let pos = match cached {
+            Some(Some(pos)) => pos,
+            _ => {
+                let mut pos = start;
+                if let Some(skipper) = self.skipper::<I>() {
+                    while let Ok((_, end)) = skipper.run(input, pos) {
+                        if end == pos {
+                            break;
+                        }
+                        pos = end;
+                    }
+                }
+                if cached.is_some() {
+                    self.skip_cache.lock().unwrap().positions.insert(start, pos);
+                }
+                pos
+            }
+        };
+        if let Some(skipped) = &mut *self.skipped.lock().unwrap() {
+            if pos > start {
+                skipped.push(start..pos);
+            }
+        }
+        pos
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R> {
+    pub fn token(self) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            let start = self.context.skip(input, start);
+            self.run(input, start)
+        })
+    }
+
+    pub fn with_coverage(
+        self,
+    ) -> Parser<'a, I, impl RawParser<I, Output = (R::Output, Vec<bool>)> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            let outer = self.context.skipped.lock().unwrap().replace(Vec::new());
+            let result = self.run(input, start);
+            let skipped = std::mem::replace(&mut *self.context.skipped.lock().unwrap(), outer);
+            let (result, end) = result?;
+            let mut mask = vec![false; input.len()];
+            mask[start..end].iter_mut().for_each(|m| *m = true);
+            for range in skipped.into_iter().flatten() {
+                mask[range].iter_mut().for_each(|m| *m = false);
+            }
+            if let Some(outer) = &mut *self.context.skipped.lock().unwrap() {
+                outer.extend((start..end).filter(|&i| !mask[i]).map(|i| i..i + 1));
+            }
+            Ok(((result, mask), end))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::parsers::*;
+
+    #[test]
+    fn cached_skipping_keeps_results() {
+        let chars = "   let   x".chars().collect::<Vec<_>>();
+        let scans = AtomicUsize::new(0);
+        let context = ParserContext::new();
+        let space = context.new_parser(|input: &Vec<char>, start| {
+            scans.fetch_add(1, Ordering::Relaxed);
+            if start < input.len() && input[start] == ' ' {
+                Ok(((), start + 1))
+            } else {
+                Err(Error::Single(0.0, start))
+            }
+        });
+        context.set_skipper((!space).into_dyn_sync());
+        let keyword = |k: &str| context.tag(k.chars().collect());
+        let cached =
+            (keyword("lex").token() | keyword("let").token()) + context.single('x').token();
+        let spaces = space.many0_progress(|_| ());
+        let uncached = ((!spaces + keyword("lex")) | (!spaces + keyword("let")))
+            + (!spaces + context.single('x'));
+
+        let expected = uncached.parse(&chars).unwrap();
+        let uncached_scans = scans.swap(0, Ordering::Relaxed);
+        assert_eq!(cached.parse(&chars).unwrap(), expected);
+        assert!(scans.load(Ordering::Relaxed) < uncached_scans);
+    }
+
+    #[test]
+    fn skip_cache_ignores_temporary_buffers() {
+        let chars = "x".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        context.set_skipper::<Vec<char>>((!context.single(' ')).into_dyn_sync());
+        let probe = context.new_parser(|_: &Vec<char>, start| {
+            let skipped = ["  a", "a  "].map(|s| context.skip(&s.chars().collect::<Vec<_>>(), 0));
+            Ok((skipped, start))
+        });
+        assert_eq!(probe.parse(&chars).unwrap(), [2, 0]);
+    }
+
+    #[test]
+    fn with_coverage_excludes_skipped_elements() {
+        let chars = "a  b c".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        context.set_skipper::<Vec<char>>((!context.single(' ')).into_dyn_sync());
+        let word = context.one_of_tokens(&['a', 'b']).token();
+        let (_, mask) = (word + word).with_coverage().parse(&chars).unwrap();
+        assert_eq!(mask, vec![true, false, false, true, false, false]);
+    }
+}
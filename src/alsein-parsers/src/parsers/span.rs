@@ -0,0 +1,66 @@
+use std::ops::Range;
+
+use super::{Parser, RawParser, Set};
+
+pub trait OffsetSpans {
+    fn offset_spans(self, base: usize) -> Self;
+}
+
+impl OffsetSpans for Range<usize> {
+    fn offset_spans(self, base: usize) -> Self {
+        self.start + base..self.end + base
+    }
+}
+
+impl<T: OffsetSpans> OffsetSpans for Option<T> {
+    fn offset_spans(self, base: usize) -> Self {
+        self.map(|v| v.offset_spans(base))
+    }
+}
+
+impl<T: OffsetSpans> OffsetSpans for Vec<T> {
+    fn offset_spans(self, base: usize) -> Self {
+        self.into_iter().map(|v| v.offset_spans(base)).collect()
+    }
+}
+
+impl<T1: OffsetSpans, T2: OffsetSpans> OffsetSpans for (T1, T2) {
+    fn offset_spans(self, base: usize) -> Self {
+        (self.0.offset_spans(base), self.1.offset_spans(base))
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R>
+where
+    R::Output: OffsetSpans,
+{
+    pub fn offset_spans(
+        self,
+        base: usize,
+    ) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            self.run(input, start)
+                .map(|(result, end)| (result.offset_spans(base), end))
+                .map_err(|e| e.offset(base))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::*;
+
+    #[test]
+    fn offset_spans_reports_host_positions() {
+        let host = "let query = ab1 and more".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let word = context.chars_while(char::is_alphabetic).offset_spans(12);
+        assert_eq!(word.parse(&host[12..15].to_vec()).unwrap(), 12..14);
+        let snippet = context.chars_while(char::is_alphabetic) + !context.single('2');
+        let error = snippet
+            .offset_spans(12)
+            .parse(&host[12..15].to_vec())
+            .unwrap_err();
+        assert_eq!(error.range(), 12..15);
+    }
+}
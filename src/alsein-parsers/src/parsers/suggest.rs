@@ -0,0 +1,48 @@
+use super::{Error, Parser, ParserContext, RawParser, Set};
+
+impl ParserContext<'_> {
+    pub(super) fn record_hint(&self, error: &Error, hint: &str) {
+        if let Some((at, hints)) = &mut *self.suggest_at.lock().unwrap() {
+            if error.range().contains(at) && !hints.iter().any(|h| h == hint) {
+                hints.push(hint.to_string());
+            }
+        }
+    }
+}
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R> {
+    pub fn suggest(&self, input: &I, at: usize) -> Vec<String> {
+        *self.context.suggest_at.lock().unwrap() = Some((at, Vec::new()));
+        let _ = self.run_top_level(input);
+        self.context.suggest_at.lock().unwrap().take().unwrap().1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::*;
+
+    #[test]
+    fn suggest_completes_keyword() {
+        let chars = "f".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let keyword = |k: &str| context.tag(k.chars().collect()).label(k);
+        let grammar = keyword("for") | keyword("fn") | keyword("let");
+        assert_eq!(grammar.suggest(&chars, 1), vec!["for", "fn"]);
+        let grammar = keyword("for") | context.tag("fx".chars().collect()).expected("an fx");
+        assert_eq!(grammar.suggest(&chars, 1), vec!["for"]);
+    }
+
+    #[test]
+    fn suggest_lists_continuations_at_eof() {
+        let chars = "if ".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let keyword = |k: &str| context.str_tag(k).label(k);
+        let condition = keyword("true") | keyword("false");
+        let statement = !context.str_tag("if ") + condition;
+        assert_eq!(statement.suggest(&chars, 3), vec!["true", "false"]);
+        let conditions = !context.str_tag("if ") + condition.many_max(usize::MAX);
+        assert!(conditions.parse(&chars).is_ok());
+        assert_eq!(conditions.suggest(&chars, 3), vec!["true", "false"]);
+    }
+}
@@ -0,0 +1,61 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use super::{Parser, RawParser};
+
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 as usize
+    }
+}
+
+pub fn check_invariants<E: Clone + 'static, R: RawParser<Vec<E>> + ?Sized>(
+    parser: Parser<'_, Vec<E>, R>,
+    alphabet: &[E],
+    cases: usize,
+) {
+    let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..cases {
+        let len = rng.next() % 16;
+        let input = (0..len)
+            .map(|_| alphabet[rng.next() % alphabet.len()].clone())
+            .collect::<Vec<_>>();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            parser.run_top_level(&input).map(|(_, end)| end)
+        }));
+        match result {
+            Err(_) => panic!("parser panicked on input of length {}", len),
+            Ok(Ok(end)) => assert!(
+                end <= len,
+                "parser ended at {} past input length {}",
+                end,
+                len
+            ),
+            Ok(Err(e)) => assert!(
+                e.range().start <= len,
+                "error range {:?} starts past input length {}",
+                e.range(),
+                len
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_invariants;
+    use crate::parsers::*;
+
+    #[test]
+    fn builtin_combinators_hold_invariants() {
+        let context = ParserContext::new();
+        let alphabet = ['a', 'b', 'c'];
+        check_invariants(context.single('a'), &alphabet, 200);
+        check_invariants(context.single('a') | context.single('b'), &alphabet, 200);
+        check_invariants(context.single('a') + context.single('b'), &alphabet, 200);
+    }
+}
@@ -0,0 +1,146 @@
+use std::ops::Range;
+
+use super::{Error, ParserContext, ParserDyn, Result, Set};
+
+pub struct Tokenizer<'a, I: Set + ?Sized, Tok> {
+    rules: ParserDyn<'a, I, Tok>,
+}
+
+impl<'a, I: Set + ?Sized, Tok> Clone for Tokenizer<'a, I, Tok> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, I: Set + ?Sized, Tok> Copy for Tokenizer<'a, I, Tok> {}
+
+impl<'a> ParserContext<'a> {
+    pub fn tokenizer<I: Set + 'a, Tok: 'a>(
+        &'a self,
+        rules: Vec<ParserDyn<'a, I, Tok>>,
+    ) -> Tokenizer<'a, I, Tok> {
+        Tokenizer {
+            rules: self.longest_match(rules.into_iter().map(|rule| (0, rule)).collect()),
+        }
+    }
+}
+
+impl<'a, I: Set + 'a, Tok: 'a> Tokenizer<'a, I, Tok> {
+    pub fn tokenize<'i>(
+        self,
+        input: &'i I,
+    ) -> impl Iterator<Item = Result<(Tok, Range<usize>)>> + use<'a, 'i, I, Tok> {
+        let context = self.rules.context;
+        context.skip_cache.lock().unwrap().reset(input);
+        context.reset();
+        let mut pos = Some(0);
+        std::iter::from_fn(move || {
+            context.clear_poison();
+            let start = context.skip(input, pos?);
+            if start >= input.len() {
+                pos = None;
+                return None;
+            }
+            let token = match self.rules.run(input, start) {
+                Ok((_, end)) if end == start => Err(Error::Single(0.0, start)),
+                Ok((token, end)) => Ok((token, start..end)),
+                Err(e) => Err(e),
+            };
+            pos = token.as_ref().ok().map(|(_, span)| span.end);
+            Some(token)
+        })
+    }
+}
+#[cfg(test)]
+mod tests {
+    use crate::parsers::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Tok {
+        Number,
+        Plus,
+        Ident,
+    }
+
+    #[test]
+    fn tokenizer_emits_spanned_tokens() {
+        let context = ParserContext::new();
+        context.set_skipper::<Vec<char>>((!context.single(' ')).into_dyn_sync());
+        let number = context
+            .chars_while(|c| c.is_ascii_digit())
+            .require_progress();
+        let ident = context
+            .chars_while(char::is_alphanumeric)
+            .require_progress();
+        let tokenizer = context.tokenizer(vec![
+            number.map(|_| Tok::Number).into_dyn(),
+            context.single('+').map(|_| Tok::Plus).into_dyn(),
+            ident.map(|_| Tok::Ident).into_dyn(),
+        ]);
+        let strict = context.tokenizer(vec![context
+            .single('#')
+            .fatal()
+            .map(|_| Tok::Plus)
+            .into_dyn()]);
+        let tokenize = |s: &str| {
+            let chars = s.chars().collect::<Vec<_>>();
+            tokenizer
+                .tokenize(&chars)
+                .collect::<Result<Vec<_>>>()
+                .unwrap()
+        };
+        assert_eq!(
+            tokenize("12 + ab"),
+            vec![(Tok::Number, 0..2), (Tok::Plus, 3..4), (Tok::Ident, 5..7)]
+        );
+        assert_eq!(
+            tokenize("  ab +  "),
+            vec![(Tok::Ident, 2..4), (Tok::Plus, 5..6)]
+        );
+        let chars = "#a".chars().collect::<Vec<_>>();
+        let error = strict.tokenize(&chars).nth(1).unwrap().unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Hard);
+        assert_eq!(
+            tokenize("ab 1"),
+            vec![(Tok::Ident, 0..2), (Tok::Number, 3..4)]
+        );
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Lexeme {
+        Word,
+        Quote,
+        Text,
+    }
+
+    #[test]
+    fn tokenizer_keeps_modes_between_tokens() {
+        let context = ParserContext::new();
+        let text = context
+            .none_of_str("\"")
+            .map(|_| Lexeme::Text)
+            .in_mode("string");
+        let close = (context.single('"') + context.pop_mode())
+            .map(|_| Lexeme::Quote)
+            .in_mode("string");
+        let open = (context.single('"') + context.push_mode("string")).map(|_| Lexeme::Quote);
+        let word = context.single('a').map(|_| Lexeme::Word);
+        let tokenizer = context.tokenizer(vec![
+            text.into_dyn(),
+            close.into_dyn(),
+            open.into_dyn(),
+            word.into_dyn(),
+        ]);
+        let chars = "a\"b\"".chars().collect::<Vec<_>>();
+        let tokens = tokenizer
+            .tokenize(&chars)
+            .map(|token| token.map(|(lexeme, _)| lexeme))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            tokens,
+            vec![Lexeme::Word, Lexeme::Quote, Lexeme::Text, Lexeme::Quote]
+        );
+        assert_eq!(context.current_mode(), None);
+    }
+}
@@ -0,0 +1,157 @@
+use super::{Error, Parser, ParserResult, RawParser, Set};
+
+pub trait Untuple<T> {
+    fn untuple(self) -> T;
+}
+
+macro_rules! impl_untuple {
+    ($nested:tt; $($done:ident)+;) => {};
+    ($nested:tt; $($done:ident)+; $next:ident $($rest:ident)*) => {
+        impl<$($done,)+ $next> Untuple<($($done,)+ $next)> for ($nested, $next) {
+            #[allow(non_snake_case)]
+            fn untuple(self) -> ($($done,)+ $next) {
+                let ($nested, $next) = self;
+                ($($done,)+ $next)
+            }
+        }
+        impl_untuple!(($nested, $next); $($done)+ $next; $($rest)*);
+    };
+}
+
+impl_untuple!((A, B); A B; C D E F G H J K L M);
+
+macro_rules! impl_sequence {
+    ($r0:ident $v0:ident, $($r:ident $v:ident $i:tt),+) => {
+        impl<'a, I: Set + ?Sized, $r0: RawParser<I> + ?Sized + 'a, $($r: RawParser<I> + ?Sized + 'a),+>
+            RawParser<I> for (Parser<'a, I, $r0>, $(Parser<'a, I, $r>),+)
+        {
+            type Output = ($r0::Output, $($r::Output),+);
+            #[allow(unused_assignments)]
+            fn parse(&self, input: &I, start: usize) -> ParserResult<Self::Output> {
+                let (mut error, mut resume, mut pos) = (None::<Error>, start, start);
+                let $v0 = match self.0.run(input, start) {
+                    Ok((v, end)) => {
+                        pos = end;
+                        Some(v)
+                    }
+                    Err(e) => {
+                        resume = self.0.raw.resume(&e);
+                        error = Some(e);
+                        None
+                    }
+                };
+                $(
+                    let $v = match error.take() {
+                        None => match self.$i.run(input, pos) {
+                            Ok((v, end)) => {
+                                pos = end;
+                                Some(v)
+                            }
+                            Err(e) => {
+                                let e = Error::Succeed(start..pos) + e;
+                                resume = e.range().end;
+                                error = Some(e);
+                                None
+                            }
+                        },
+                        Some(e) if resume > input.len() => return Err(e),
+                        Some(e) => {
+                            let e = match self.$i.run(input, resume) {
+                                Ok((_, end)) => e + Error::Succeed(resume..end),
+                                Err(e2) => e + e2,
+                            };
+                            resume = e.range().end;
+                            error = Some(e);
+                            None
+                        }
+                    };
+                )+
+                match error {
+                    Some(e) => Err(e),
+                    None => Ok((($v0.unwrap(), $($v.unwrap()),+), pos)),
+                }
+            }
+        }
+    };
+}
+
+impl_sequence!(R0 v0, R1 v1 1);
+impl_sequence!(R0 v0, R1 v1 1, R2 v2 2);
+impl_sequence!(R0 v0, R1 v1 1, R2 v2 2, R3 v3 3);
+impl_sequence!(R0 v0, R1 v1 1, R2 v2 2, R3 v3 3, R4 v4 4);
+impl_sequence!(R0 v0, R1 v1 1, R2 v2 2, R3 v3 3, R4 v4 4, R5 v5 5);
+impl_sequence!(R0 v0, R1 v1 1, R2 v2 2, R3 v3 3, R4 v4 4, R5 v5 5, R6 v6 6);
+impl_sequence!(R0 v0, R1 v1 1, R2 v2 2, R3 v3 3, R4 v4 4, R5 v5 5, R6 v6 6, R7 v7 7);
+impl_sequence!(R0 v0, R1 v1 1, R2 v2 2, R3 v3 3, R4 v4 4, R5 v5 5, R6 v6 6, R7 v7 7, R8 v8 8);
+impl_sequence!(R0 v0, R1 v1 1, R2 v2 2, R3 v3 3, R4 v4 4, R5 v5 5, R6 v6 6, R7 v7 7, R8 v8 8, R9 v9 9);
+impl_sequence!(R0 v0, R1 v1 1, R2 v2 2, R3 v3 3, R4 v4 4, R5 v5 5, R6 v6 6, R7 v7 7, R8 v8 8, R9 v9 9, R10 v10 10);
+impl_sequence!(R0 v0, R1 v1 1, R2 v2 2, R3 v3 3, R4 v4 4, R5 v5 5, R6 v6 6, R7 v7 7, R8 v8 8, R9 v9 9, R10 v10 10, R11 v11 11);
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R> {
+    pub fn untuple<T: 'a>(self) -> Parser<'a, I, impl RawParser<I, Output = T> + 'a>
+    where
+        R::Output: Untuple<T>,
+    {
+        self.map(Untuple::untuple)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::*;
+
+    #[test]
+    fn untuple_flattens_three() {
+        let chars = "abc".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let abc = context.single('a') + context.single('b') + context.single('c');
+        assert_eq!(abc.untuple().parse(&chars).unwrap(), ('a', 'b', 'c'));
+    }
+
+    #[test]
+    fn untuple_flattens_four() {
+        let chars = "abcd".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let abcd =
+            context.single('a') + context.single('b') + context.single('c') + context.single('d');
+        let (a, b, c, d) = abcd.untuple().parse(&chars).unwrap();
+        assert_eq!([a, b, c, d], ['a', 'b', 'c', 'd']);
+    }
+
+    #[test]
+    fn tuple_sequences_flat_and_matches_add_errors() {
+        let context = ParserContext::new();
+        let [a, b, c] = ['a', 'b', 'c'].map(|t| context.single(t));
+        let abc = context.new_parser((a, b, c));
+        let chars = "abc".chars().collect::<Vec<_>>();
+        assert_eq!(abc.parse(&chars).unwrap(), ('a', 'b', 'c'));
+        for s in ["xbc", "axc", "abx", "xxc", "a"] {
+            let chars = s.chars().collect::<Vec<_>>();
+            assert_eq!(
+                format!("{:?}", abc.parse(&chars).unwrap_err()),
+                format!("{:?}", (a + b + c).parse(&chars).unwrap_err())
+            );
+        }
+    }
+
+    #[test]
+    fn untuple_flattens_twelve() {
+        let chars = "abcdefghijkl".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let p = |c| context.single(c);
+        let all = p('a')
+            + p('b')
+            + p('c')
+            + p('d')
+            + p('e')
+            + p('f')
+            + p('g')
+            + p('h')
+            + p('i')
+            + p('j')
+            + p('k')
+            + p('l');
+        let (a, _, _, _, _, _, _, _, _, _, _, l) = all.untuple().parse(&chars).unwrap();
+        assert_eq!((a, l), ('a', 'l'));
+    }
+}
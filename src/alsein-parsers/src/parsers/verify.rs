@@ -0,0 +1,69 @@
+use super::{Error, Parser, RawParser, Set};
+
+impl<'a, I: Set + ?Sized, R: RawParser<I> + ?Sized> Parser<'a, I, R>
+where
+    R::Output: PartialEq + 'static,
+{
+    pub fn in_set(
+        self,
+        table: &'a dyn Set<Output = R::Output>,
+    ) -> Parser<'a, I, impl RawParser<I, Output = R::Output> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            let (result, end) = self.run(input, start)?;
+            if (0..table.len()).any(|i| table.get(i) == &result) {
+                Ok((result, end))
+            } else {
+                Err(Error::Single(0.0, start))
+            }
+        })
+    }
+}
+
+impl<'a, I: Set + ?Sized, T, R: RawParser<I, Output = Vec<T>> + ?Sized> Parser<'a, I, R> {
+    pub fn verify_count(
+        self,
+        expected: usize,
+    ) -> Parser<'a, I, impl RawParser<I, Output = Vec<T>> + 'a> {
+        self.context.new_parser(move |input: &I, start| {
+            let (result, end) = self.run(input, start)?;
+            if result.len() == expected {
+                Ok((result, end))
+            } else {
+                Err(Error::Hinted(
+                    Box::new(Error::Single(0.0, start)),
+                    format!("expected {} items, found {}", expected, result.len()),
+                ))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parsers::*;
+
+    #[test]
+    fn in_set_accepts_known_identifiers() {
+        let context = ParserContext::new();
+        let table = vec!["foo".chars().collect::<Vec<_>>()];
+        let identifier =
+            context.tag("foo".chars().collect()) | context.tag("bar".chars().collect());
+        let known = identifier.in_set(&table);
+        assert!(known.parse(&"foo".chars().collect::<Vec<_>>()).is_ok());
+        assert!(identifier.parse(&"bar".chars().collect::<Vec<_>>()).is_ok());
+        assert!(known.parse(&"bar".chars().collect::<Vec<_>>()).is_err());
+    }
+
+    #[test]
+    fn verify_count_reports_counts() {
+        let chars = "ab".chars().collect::<Vec<_>>();
+        let context = ParserContext::new();
+        let row = context
+            .one_of_tokens(&['a', 'b'])
+            .many_max(usize::MAX)
+            .verify_count(3);
+        let error = row.parse(&chars).unwrap_err();
+        assert_eq!(error.to_string(), "expected 3 items, found 2");
+        assert_eq!(row.parse(&"abb".chars().collect()).unwrap().len(), 3);
+    }
+}
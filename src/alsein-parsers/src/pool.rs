@@ -1,14 +1,22 @@
-use std::{collections::HashMap, marker::PhantomData, mem, sync::Mutex};
+use std::{collections::HashMap, marker::PhantomData, sync::Mutex};
+
+type DropFn = unsafe fn((usize, usize));
+
+#[derive(Default)]
+struct Values(Mutex<HashMap<(usize, usize), DropFn>>);
 
 #[derive(Default)]
 pub struct Pool<'a> {
-    values: Mutex<HashMap<(usize, usize), unsafe fn((usize, usize))>>,
+    values: Values,
     _phantom: PhantomData<&'a ()>,
 }
 
+#[repr(C)]
+struct PtrExtra<T: ?Sized>(*mut T, usize);
+
 unsafe fn drop_ptr<T: ?Sized>(ptr: (usize, usize)) {
-    let ptr = &mut **mem::transmute::<_, *mut *mut T>(&ptr);
-    Box::from_raw(ptr);
+    let ptr = *(&ptr as *const (usize, usize) as *const *mut T);
+    drop(Box::from_raw(ptr));
 }
 
 impl<'a> Pool<'a> {
@@ -18,15 +26,15 @@ impl<'a> Pool<'a> {
 
     fn stored_ptr<T: ?Sized>(ptr: *mut T) -> (usize, usize) {
         unsafe {
-            let ptr_extra = (ptr, 0usize);
-            **mem::transmute::<_, *mut *mut (usize, usize)>(&ptr_extra)
+            let ptr_extra = PtrExtra(ptr, 0);
+            *(&ptr_extra as *const PtrExtra<T> as *const (usize, usize))
         }
     }
 
     pub fn add<T: ?Sized>(&self, item: Box<T>) -> &'a mut T {
         unsafe {
             let ptr = Box::into_raw(item);
-            let mut values = self.values.lock().unwrap();
+            let mut values = self.values.0.lock().unwrap();
             values.insert(Self::stored_ptr(ptr), drop_ptr::<T>);
             &mut *ptr
         }
@@ -35,15 +43,15 @@ impl<'a> Pool<'a> {
     pub fn remove<T: ?Sized>(&'a self, item: &mut T) -> Option<Box<T>> {
         unsafe {
             let ptr = Self::stored_ptr(item);
-            let mut values = self.values.lock().unwrap();
+            let mut values = self.values.0.lock().unwrap();
             values.remove(&ptr).map(move |_| Box::from_raw(item))
         }
     }
 }
 
-impl<'a> Drop for Pool<'a> {
+impl Drop for Values {
     fn drop(&mut self) {
-        let values = self.values.lock().unwrap();
+        let values = self.0.lock().unwrap();
         for (ptr, drop_ptr) in &*values {
             unsafe {
                 drop_ptr(*ptr);